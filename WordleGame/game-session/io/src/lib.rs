@@ -1,25 +1,99 @@
 #![no_std]
 
-use gmeta::{In, InOut, Metadata, Out};
+use gmeta::{In, InOut, Metadata};
 use gstd::{prelude::*, ActorId, MessageId, PartialEq};
 use wordle_io::*;
 
 pub struct GameSessionMetadata;
 
 impl Metadata for GameSessionMetadata {
-    type Init = In<ActorId>;
+    type Init = In<SessionInit>;
     type Handle = InOut<SessionAction, SessionEvent>;
     type Others = ();
     type Reply = ();
     type Signal = ();
-    type State = Out<Session>;
+    type State = InOut<StateQuery, StateReply>;
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct SessionInit {
+    pub target_program_id: ActorId,
+    /// Length of the words of the target wordle, reported to clients.
+    pub word_length: u8,
+    /// Rules of games started without their own rules.
+    pub rules: Rules,
+    pub bounds: RuleBounds,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct Rules {
+    /// Guesses allowed in a single-board game, every extra board adds one more.
+    pub max_attempts: u8,
+    /// Blocks after the start of a game at which it is lost.
+    pub timeout_blocks: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            timeout_blocks: 200,
+        }
+    }
+}
+
+/// Inclusive limits for the rules a game may be started with.
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct RuleBounds {
+    pub min_attempts: u8,
+    pub max_attempts: u8,
+    pub min_timeout_blocks: u32,
+    pub max_timeout_blocks: u32,
+}
+
+impl Default for RuleBounds {
+    fn default() -> Self {
+        Self {
+            min_attempts: 1,
+            max_attempts: 10,
+            min_timeout_blocks: 20,
+            max_timeout_blocks: 1_000,
+        }
+    }
+}
+
+impl RuleBounds {
+    /// Whether every minimum is at most its maximum.
+    pub fn is_valid(&self) -> bool {
+        self.min_attempts <= self.max_attempts && self.min_timeout_blocks <= self.max_timeout_blocks
+    }
+
+    pub fn allow(&self, rules: &Rules) -> bool {
+        (self.min_attempts..=self.max_attempts).contains(&rules.max_attempts)
+            && (self.min_timeout_blocks..=self.max_timeout_blocks).contains(&rules.timeout_blocks)
+    }
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum SessionAction {
-    StartGame { user: ActorId },
+    /// Starts a game by the default rules, or by `rules` if they are within the bounds.
+    /// A finished game is moved to the session history first, a game in progress
+    /// must be finished before.
+    StartGame { user: ActorId, hard_mode: bool, mode: GameMode, language: Language, rules: Option<Rules> },
     CheckWord { user: ActorId, word: String },
     CheckGameStatus { user: ActorId },
+    /// Sent by the program to itself to end the game `game_id` of `user` once
+    /// its time is up.
+    Timeout { user: ActorId, game_id: u64 },
+    /// Abandons the request still waiting for a wordle reply, the guess is not counted.
+    Restart { user: ActorId },
+    /// Owner only: allows `relayer` to act on behalf of any player.
+    AddRelayer { relayer: ActorId },
+    /// Owner only.
+    RemoveRelayer { relayer: ActorId },
+    /// Owner only: sets the default rules and the bounds for games' own rules.
+    /// Games in progress keep their rules.
+    SetRules { rules: Rules, bounds: RuleBounds },
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
@@ -31,29 +105,175 @@ pub enum GameResult {
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum SessionEvent {
     GameStarted { user: ActorId },
-    WordChecked { user: ActorId, correct_positions: Vec<u8>, contained_in_word: Vec<u8> },
+    WordChecked { user: ActorId, correct_positions: Vec<u8>, contained_in_word: Vec<u8>, keyboard: Keyboard },
+    BoardsChecked { user: ActorId, boards: Vec<BoardFeedback>, keyboard: Keyboard },
+    HardModeViolation { user: ActorId, violation: HardModeViolation },
+    /// The final event of a game, also sent to the player when the game times
+    /// out. `word` is the hidden word, separated by ", " from the others in a
+    /// multi-board game; an unsolved adversarial game lists every answer still
+    /// possible. It is `None` when wordle did not reveal the word, as while
+    /// others still play a daily or race word.
+    GameOver { result: GameResult, word: Option<String>, guesses: u8 },
     GameStatus(GameStatus),
+    Restarted,
+    RelayerAdded { relayer: ActorId },
+    RelayerRemoved { relayer: ActorId },
+    RulesSet { rules: Rules, bounds: RuleBounds },
     GameError(String),
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub struct Session {
     pub target_program_id: ActorId,
+    /// Number of the game being played, `None` between games. Timeouts of
+    /// other games are ignored.
+    pub game_id: Option<u64>,
     pub session_status: SessionStatus,
     pub game_status: GameStatus,
+    /// The request in flight to wordle and the message waiting for its reply.
     pub msg_ids: Option<(MessageId, MessageId)>,
     pub guess_count: u8,
+    /// Every checked guess of the current game, in order.
+    pub guesses: Vec<GuessRecord>,
+    /// Rules of the current game.
+    pub rules: Rules,
+    pub solved_boards: Vec<bool>,
+    pub keyboard: Keyboard,
+    pub start_block: u32,
+    /// Earlier games of the player, oldest first.
+    pub history: Vec<FinishedGame>,
+}
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateQuery {
+    /// The session of one player.
+    Session(ActorId),
+    Sessions,
+    Stats(ActorId),
+    Relayers,
+    Rules,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Session(Option<Session>),
+    Sessions(Vec<(ActorId, Session)>),
+    Stats(Option<PlayerStats>),
+    Relayers(Vec<ActorId>),
+    Rules { word_length: u8, rules: Rules, bounds: RuleBounds },
+}
+
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub games_won: u32,
+    pub current_streak: u32,
+    pub max_streak: u32,
+    /// `guess_distribution[i]` is the number of games won with `i + 1` guesses.
+    pub guess_distribution: Vec<u32>,
+}
+
+impl PlayerStats {
+    pub fn win_percentage(&self) -> u8 {
+        if self.games_played == 0 {
+            return 0;
+        }
+        (self.games_won as u64 * 100 / self.games_played as u64) as u8
+    }
+
+    pub fn record(&mut self, result: &GameResult, guesses: u8) {
+        self.games_played += 1;
+        match result {
+            GameResult::Win => {
+                self.games_won += 1;
+                self.current_streak += 1;
+                self.max_streak = self.max_streak.max(self.current_streak);
+                let guesses = guesses.max(1) as usize;
+                if self.guess_distribution.len() < guesses {
+                    self.guess_distribution.resize(guesses, 0);
+                }
+                self.guess_distribution[guesses - 1] += 1;
+            }
+            GameResult::Lose => self.current_streak = 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct GuessRecord {
+    pub word: String,
+    /// Feedback for each board of the game.
+    pub boards: Vec<BoardFeedback>,
+    pub block: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct FinishedGame {
+    pub game_id: u64,
+    pub result: GameResult,
+    pub guesses: Vec<GuessRecord>,
+    pub rules: Rules,
     pub start_block: u32,
 }
+
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub struct GameStatus {
     pub game_result: Option<GameResult>,
+    /// Both budgets are zero once the game is over.
+    pub attempts_remaining: u8,
+    pub blocks_remaining: u32,
+}
+
+/// Ordered from the least to the most informative status.
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LetterStatus {
+    Absent,
+    Present,
+    Correct,
+}
+
+/// Best known status of every guessed letter, sorted by letter. In a
+/// multi-board game a letter gets the best status it has on any board.
+///
+/// This is not a fixed 26-letter map: games are played in several languages,
+/// whose alphabets differ in size and use multibyte letters. Letters that have
+/// not been guessed yet are not listed, and clients must show them as unknown.
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct Keyboard {
+    pub letters: Vec<(String, LetterStatus)>,
+}
+
+impl Keyboard {
+    pub fn status(&self, letter: char) -> Option<LetterStatus> {
+        let letter = letter.to_string();
+        self.letters
+            .binary_search_by(|(known, _)| known.cmp(&letter))
+            .ok()
+            .map(|i| self.letters[i].1)
+    }
+
+    /// Records the feedback to a guess, a letter's status is never downgraded.
+    pub fn update(&mut self, word: &str, feedback: &BoardFeedback) {
+        for (position, letter) in word.to_lowercase().chars().enumerate() {
+            let position = position as u8;
+            let status = if feedback.correct_positions.contains(&position) {
+                LetterStatus::Correct
+            } else if feedback.contained_in_word.contains(&position) {
+                LetterStatus::Present
+            } else {
+                LetterStatus::Absent
+            };
+            let letter = letter.to_string();
+            match self.letters.binary_search_by(|(known, _)| known.cmp(&letter)) {
+                Ok(i) => self.letters[i].1 = self.letters[i].1.max(status),
+                Err(i) => self.letters.insert(i, (letter, status)),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
 pub enum SessionStatus {
     Waiting,
     MessageSent,
-    MessageReceived(Event),
     GameEnded { result: GameResult },
 }
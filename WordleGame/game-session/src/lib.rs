@@ -1,161 +1,540 @@
 #![no_std]
 
-use gstd::{debug, exec, msg, prelude::*};
+use gstd::{
+    collections::{HashMap, HashSet},
+    debug,
+    errors::Error,
+    exec, msg,
+    prelude::*,
+    ActorId,
+};
 use wordle_io::*;
 use game_session_io::*;
 
-static mut SESSION: Option<Session> = None;
+// Blocks to wait for a wordle reply before the round is abandoned.
+const REPLY_TIMEOUT: u32 = 10;
+
+static mut GAME_SESSIONS: Option<GameSessions> = None;
+
+#[derive(Default)]
+struct GameSessions {
+    owner: ActorId,
+    target_program_id: ActorId,
+    word_length: u8,
+    rules: Rules,
+    bounds: RuleBounds,
+    // Accounts allowed to send actions on behalf of other players.
+    relayers: HashSet<ActorId>,
+    // One session per player, keyed by the player's address.
+    sessions: HashMap<ActorId, Session>,
+    // Results of every finished game, kept across games.
+    stats: HashMap<ActorId, PlayerStats>,
+}
+
+// Why a request to wordle did not produce an event.
+enum RequestError {
+    // Wordle rejected the request.
+    Game(WordleError),
+    // Wordle failed, sent an undecodable reply or did not reply in time.
+    Failed(&'static str),
+    // `Restart` abandoned the round while the request was in flight.
+    Abandoned,
+}
+
+impl RequestError {
+    fn into_event(self) -> SessionEvent {
+        let message = match self {
+            RequestError::Game(error) => error_message(&error),
+            RequestError::Failed(message) => message.into(),
+            RequestError::Abandoned => "The request was abandoned".into(),
+        };
+        SessionEvent::GameError(message)
+    }
+}
 
 #[no_mangle]
 extern "C" fn init() {
     debug!("===INIT===");
-    let target_program_id = msg::load().expect("Unable to decode Init");
+    let SessionInit { target_program_id, word_length, rules, bounds } =
+        msg::load().expect("Unable to decode Init");
+    assert!(word_length > 0, "The word length must be positive");
+    assert!(bounds.is_valid(), "A minimum of the bounds is above its maximum");
+    assert!(bounds.allow(&rules), "The default rules are out of bounds");
 
     unsafe {
-        SESSION = Some(Session {
+        GAME_SESSIONS = Some(GameSessions {
+            owner: msg::source(),
             target_program_id,
-            session_status: SessionStatus::Waiting,
-            game_status: GameStatus { game_result: None },
-            msg_ids: Some((msg::id(), msg::id())),
-            guess_count: 0,
-            start_block: exec::block_height(),
+            word_length,
+            rules,
+            bounds,
+            ..Default::default()
         });
     }
 }
 
-#[no_mangle]
-extern "C" fn handle() {
+#[gstd::async_main]
+async fn main() {
     debug!("===HANDLE START===");
-    let session = unsafe { SESSION.as_mut().expect("The session is not initialized") };
-    debug!("---SESSION: {:?}---", session);
     let action: SessionAction = msg::load().expect("Unable to decode `Action`");
     debug!("---SESSION ACTION: {:?}---", action);
 
-    match &session.session_status {
-        SessionStatus::Waiting => {
-            match action {
-                SessionAction::StartGame { user } => {
-                    debug!("===WAITING AND START GAME===");
-                    msg::send(session.target_program_id, Action::StartGame { user }, 0)
-                        .expect("Error in sending a message");
-                    session.session_status = SessionStatus::MessageSent;
-                    exec::wait();
-                }
-                SessionAction::CheckWord { user, word } => {
-                    debug!("===CHECK WORD FOR USER: {:?}===", user);
-
-                    if session.guess_count >= 6 {
-                        msg::reply(SessionEvent::GameError("Game over: Too many guesses".into()), 0)
-                            .expect("Unable to reply");
-                        return;
-                    }
-
-                    session.guess_count += 1;
-                    let current_game_status = get_game_status();
-
-                    if current_game_status.game_result.is_some() {
-                        debug!("===GAME RESULT IS FIXED FOR USER: {:?}===", user);
-                        msg::reply(SessionEvent::GameStatus(current_game_status.clone()), 0)
-                            .expect("Unable to reply");
-                    } else {
-                        msg::send(session.target_program_id, Action::CheckWord { user, word: word.clone() }, 0)
-                            .expect("Error in sending a message");
-                        session.session_status = SessionStatus::MessageSent;
-                        exec::wait();
-                    }
-                }
-                SessionAction::CheckGameStatus { user:_ } => {
-                    debug!("===CHECK GAME STATUS===");
-                    let current_block = exec::block_height() as u64;
-
-                    if current_block >= (session.start_block + 200).into() {
-                        session.session_status = SessionStatus::GameEnded { result: GameResult::Lose };
-                        let current_game_status = get_game_status();
-                        msg::reply(SessionEvent::GameStatus(current_game_status.clone()), 0)
-                            .expect("Unable to reply");
-                    } else {
-                        let current_game_status = get_game_status();
-                        msg::reply(SessionEvent::GameStatus(current_game_status.clone()), 0)
-                            .expect("Unable to reply");
-                    }
-                }
+    let GameSessions { owner, relayers, rules: default_rules, bounds: default_bounds, .. } =
+        game_sessions();
+    let source = msg::source();
+    let event = match action {
+        SessionAction::StartGame { user, .. }
+        | SessionAction::CheckWord { user, .. }
+        | SessionAction::CheckGameStatus { user }
+        | SessionAction::Restart { user }
+            // Players act for themselves, unless a relayer acts for them.
+            if user != source && !relayers.contains(&source) =>
+        {
+            SessionEvent::GameError("The sender may not act on behalf of this player".into())
+        }
+        SessionAction::StartGame { user, hard_mode, mode, language, rules } => {
+            start_game(user, hard_mode, mode, language, rules).await
+        }
+        SessionAction::CheckWord { user, word } => check_word(user, word).await,
+        SessionAction::CheckGameStatus { user } => check_game_status(user).await,
+        SessionAction::Timeout { .. } if source != exec::program_id() => {
+            SessionEvent::GameError("Only the session program can time games out".into())
+        }
+        SessionAction::Timeout { user, game_id } => timeout(user, game_id).await,
+        SessionAction::Restart { user } => restart(user),
+        SessionAction::AddRelayer { .. } | SessionAction::RemoveRelayer { .. } if source != *owner => {
+            SessionEvent::GameError("Only the owner can manage relayers".into())
+        }
+        SessionAction::SetRules { .. } if source != *owner => {
+            SessionEvent::GameError("Only the owner can set the rules".into())
+        }
+        SessionAction::SetRules { bounds, .. } if !bounds.is_valid() => {
+            SessionEvent::GameError("A minimum of the bounds is above its maximum".into())
+        }
+        SessionAction::SetRules { rules, bounds } => {
+            if bounds.allow(&rules) {
+                *default_rules = rules;
+                *default_bounds = bounds;
+                SessionEvent::RulesSet { rules, bounds }
+            } else {
+                SessionEvent::GameError("The default rules are out of bounds".into())
             }
         }
-        SessionStatus::MessageSent => {
-            debug!("===MESSAGE SENT===");
-            msg::reply(SessionEvent::GameError("Message has already been sent, restart the game".into()), 0)
-                .expect("Error in sending a reply");
-        }
-        SessionStatus::MessageReceived(event) => {
-            debug!("===MESSAGE RECEIVED===");
-            let session_event;
-            match event {
-                Event::GameStarted { user } => {
-                    session_event = SessionEvent::GameStarted { user: *user };
-                    msg::send_delayed(exec::program_id(), SessionAction::CheckGameStatus { user: *user }, 0, 200)
-                        .expect("Failed to send delayed message");
-                    msg::reply(session_event.clone(), 0).expect("Error in sending a reply");
-                }
-                Event::WordChecked { user, ref correct_positions, ref contained_in_word } => {
-                    let mut current_game_status = get_game_status();
-                    if correct_positions.len() == 5 {
-                        current_game_status.game_result = Some(GameResult::Win);
-                        session_event = SessionEvent::GameStatus(current_game_status.clone());
-                        session.session_status = SessionStatus::GameEnded { result: GameResult::Win };
-                    } else if session.guess_count >= 6 {
-                        current_game_status.game_result = Some(GameResult::Lose);
-                        session_event = SessionEvent::GameStatus(current_game_status.clone());
-                        session.session_status = SessionStatus::GameEnded { result: GameResult::Lose };
-                    } else {
-                        session_event = SessionEvent::WordChecked {
-                            user: *user,
-                            correct_positions: correct_positions.to_vec(),
-                            contained_in_word: contained_in_word.to_vec(),
-                        };
-                    }
-                    msg::reply(session_event.clone(), 0).expect("Error in sending a reply");
+        SessionAction::AddRelayer { relayer } => {
+            relayers.insert(relayer);
+            SessionEvent::RelayerAdded { relayer }
+        }
+        SessionAction::RemoveRelayer { relayer } => {
+            relayers.remove(&relayer);
+            SessionEvent::RelayerRemoved { relayer }
+        }
+    };
+    msg::reply(event, 0).expect("Unable to reply");
+    debug!("===HANDLE ENDED===");
+}
+
+async fn start_game(
+    user: ActorId,
+    hard_mode: bool,
+    mode: GameMode,
+    language: Language,
+    rules: Option<Rules>,
+) -> SessionEvent {
+    let GameSessions { target_program_id, rules: default_rules, bounds, sessions, .. } = game_sessions();
+    let rules = match rules {
+        Some(rules) if !bounds.allow(&rules) => {
+            return SessionEvent::GameError("The rules are out of the owner's bounds".into());
+        }
+        Some(rules) => rules,
+        None => *default_rules,
+    };
+    let session = sessions.entry(user).or_insert_with(|| new_session(*target_program_id));
+    match session.session_status {
+        SessionStatus::Waiting if session.game_id.is_some() => {
+            return SessionEvent::GameError("A game is already in progress, finish it first".into());
+        }
+        SessionStatus::Waiting => {}
+        SessionStatus::MessageSent => return message_sent(),
+        SessionStatus::GameEnded { .. } => archive_game(session),
+    }
+
+    debug!("===WAITING AND START GAME===");
+    match request(user, Action::StartGame { user, hard_mode, mode, language }).await {
+        Ok(Event::GameStarted { commitments, .. }) => {
+            // The round is cleared when the previous game is archived, only the
+            // new game's settings are left to fill in.
+            let session = session_mut(user);
+            let game_id = current_game_id(session);
+            session.game_id = Some(game_id);
+            session.rules = rules;
+            session.start_block = exec::block_height();
+            session.solved_boards = vec![false; commitments.len()];
+            let timeout = SessionAction::Timeout { user, game_id };
+            msg::send_delayed(exec::program_id(), timeout, 0, rules.timeout_blocks)
+                .expect("Failed to send delayed message");
+            SessionEvent::GameStarted { user }
+        }
+        Ok(_) => unexpected_reply(),
+        Err(error) => error.into_event(),
+    }
+}
+
+async fn check_word(user: ActorId, word: String) -> SessionEvent {
+    debug!("===CHECK WORD FOR USER: {:?}===", user);
+    let Some(session) = game_sessions().sessions.get_mut(&user) else {
+        return no_game();
+    };
+    match session.session_status {
+        SessionStatus::Waiting => {}
+        SessionStatus::MessageSent => return message_sent(),
+        SessionStatus::GameEnded { .. } => return SessionEvent::GameStatus(game_status(session)),
+    }
+    if session.game_id.is_none() {
+        return no_game();
+    }
+    if session.guess_count >= max_guesses(session) {
+        return SessionEvent::GameError("Game over: Too many guesses".into());
+    }
+    if session.game_status.game_result.is_some() {
+        debug!("===GAME RESULT IS FIXED FOR USER: {:?}===", user);
+        return SessionEvent::GameStatus(game_status(session));
+    }
+
+    // Only checked guesses count, rejected and failed ones do not.
+    match request(user, Action::CheckWord { user, word: word.clone() }).await {
+        Ok(Event::WordChecked { correct_positions, contained_in_word, .. }) => {
+            let session = session_mut(user);
+            session.guess_count += 1;
+            let feedback = BoardFeedback { correct_positions, contained_in_word };
+            session.keyboard.update(&word, &feedback);
+            let solved = is_solved(&word, &feedback);
+            session.guesses.push(GuessRecord {
+                word,
+                boards: vec![feedback.clone()],
+                block: exec::block_height(),
+            });
+            if solved {
+                reveal_word(user, GameResult::Win).await
+            } else if session.guess_count >= max_guesses(session) {
+                reveal_word(user, GameResult::Lose).await
+            } else {
+                SessionEvent::WordChecked {
+                    user,
+                    correct_positions: feedback.correct_positions,
+                    contained_in_word: feedback.contained_in_word,
+                    keyboard: session.keyboard.clone(),
                 }
-            };
-            if !matches!(session.session_status, SessionStatus::GameEnded { .. }) {
-                session.session_status = SessionStatus::Waiting;
             }
         }
-        SessionStatus::GameEnded { result: _ } => {
-            msg::reply(SessionEvent::GameStatus(get_game_status()), 0)
-                .expect("Unable to reply");
+        Ok(Event::BoardsChecked { boards, .. }) => {
+            let session = session_mut(user);
+            session.guess_count += 1;
+            for (solved, board) in session.solved_boards.iter_mut().zip(&boards) {
+                *solved |= is_solved(&word, board);
+                session.keyboard.update(&word, board);
+            }
+            session.guesses.push(GuessRecord {
+                word,
+                boards: boards.clone(),
+                block: exec::block_height(),
+            });
+            if session.solved_boards.iter().all(|solved| *solved) {
+                reveal_word(user, GameResult::Win).await
+            } else if session.guess_count >= max_guesses(session) {
+                reveal_word(user, GameResult::Lose).await
+            } else {
+                SessionEvent::BoardsChecked { user, boards, keyboard: session.keyboard.clone() }
+            }
         }
+        Ok(Event::HardModeViolation { violation, .. }) => SessionEvent::HardModeViolation { user, violation },
+        Ok(_) => unexpected_reply(),
+        Err(error) => error.into_event(),
+    }
+}
+
+async fn check_game_status(user: ActorId) -> SessionEvent {
+    debug!("===CHECK GAME STATUS===");
+    let Some(session) = game_sessions().sessions.get_mut(&user) else {
+        return no_game();
     };
-    debug!("===HANDLE ENDED===");
+    match session.session_status {
+        SessionStatus::Waiting => {}
+        SessionStatus::MessageSent => return message_sent(),
+        SessionStatus::GameEnded { .. } => return SessionEvent::GameStatus(game_status(session)),
+    }
+    if session.game_id.is_none() {
+        return no_game();
+    }
+
+    let deadline = session.start_block.saturating_add(session.rules.timeout_blocks);
+    if exec::block_height() >= deadline {
+        reveal_word(user, GameResult::Lose).await
+    } else {
+        SessionEvent::GameStatus(game_status(session))
+    }
 }
 
-#[no_mangle]
-extern "C" fn handle_reply() {
-    let _reply_to = msg::reply_to().expect("Failed to query reply_to data");
-    let session = unsafe { SESSION.as_mut().expect("The session is not initialized") };
-    debug!("===HANDLE START ENDED===");
+// Ends the game whatever the session is doing, a request in flight is
+// abandoned. The player gets the final event without asking for it.
+async fn timeout(user: ActorId, game_id: u64) -> SessionEvent {
+    let session = session_mut(user);
+    let game_over = matches!(session.session_status, SessionStatus::GameEnded { .. })
+        || session.game_status.game_result.is_some();
+    if session.game_id != Some(game_id) || game_over {
+        return SessionEvent::GameError("The timeout is stale".into());
+    }
 
-    let event: Event = msg::load().expect("Unable to decode `Event`");
-    debug!("===HANDLE LLLLLLL ENDED===");
+    debug!("===GAME TIMEOUT FOR USER: {:?}===", user);
+    session.msg_ids = None;
+    session.session_status = SessionStatus::Waiting;
+    let event = reveal_word(user, GameResult::Lose).await;
+    msg::send(user, event.clone(), 0).expect("Error in sending a message");
+    event
+}
 
-    session.session_status = SessionStatus::MessageReceived(event);
+fn restart(user: ActorId) -> SessionEvent {
+    let Some(session) = game_sessions().sessions.get_mut(&user) else {
+        return no_game();
+    };
+    if session.session_status != SessionStatus::MessageSent {
+        return SessionEvent::GameError("There is no request to restart".into());
+    }
 
-    if let Some((_, original_message_id)) = session.msg_ids {
-        debug!("===HANDLE REPLY ENDED===");
+    // The waiting message sees that its request is no longer recorded and drops the reply.
+    session.msg_ids = None;
+    session.session_status = SessionStatus::Waiting;
+    if let Some(result) = session.game_status.game_result.clone() {
+        // Only the word was still to come, the game is over anyway.
+        finish_game(user, result);
+    }
+    SessionEvent::Restarted
+}
 
-        let _ = exec::wake(original_message_id);
+// Fixes the result and asks wordle for the word. The game ends even if the
+// word cannot be revealed.
+async fn reveal_word(user: ActorId, result: GameResult) -> SessionEvent {
+    session_mut(user).game_status.game_result = Some(result.clone());
+    let word = match request(user, Action::Reveal { user }).await {
+        Ok(Event::WordRevealed { words, .. }) => Some(words.join(", ")),
+        // `Restart` has already ended the game.
+        Err(RequestError::Abandoned) => return RequestError::Abandoned.into_event(),
+        // The game is over all the same, even if wordle keeps the word for now
+        // or fails to reveal it.
+        _ => None,
+    };
+    finish_game(user, result.clone());
+    SessionEvent::GameOver {
+        result,
+        word,
+        guesses: session_mut(user).guess_count,
     }
 }
 
+// Sends a request to wordle and waits for the reply. The session records the
+// request, so that a reply that arrives after `Restart` is dropped.
+async fn request(user: ActorId, action: Action) -> Result<Event, RequestError> {
+    let session = session_mut(user);
+    let reply = msg::send_for_reply_as::<_, Result<Event, WordleError>>(session.target_program_id, action, 0, 0)
+        .expect("Error in sending a message")
+        .up_to(Some(REPLY_TIMEOUT))
+        .expect("Invalid reply timeout");
+    let msg_ids = (reply.waiting_reply_to, msg::id());
+    session.msg_ids = Some(msg_ids);
+    session.session_status = SessionStatus::MessageSent;
+
+    let reply = reply.await;
+
+    // Other messages may have changed the sessions while this one was waiting.
+    let session = session_mut(user);
+    if session.msg_ids != Some(msg_ids) {
+        return Err(RequestError::Abandoned);
+    }
+    session.session_status = SessionStatus::Waiting;
+    match reply {
+        Ok(Ok(event)) => Ok(event),
+        Ok(Err(error)) => Err(RequestError::Game(error)),
+        Err(Error::Timeout(..)) => Err(RequestError::Failed("The game did not reply in time, try again")),
+        Err(Error::Decode(_)) => Err(RequestError::Failed("The game sent a reply that could not be decoded")),
+        // Wordle panicked, ran out of gas or could not be reached.
+        Err(error) => {
+            debug!("---REPLY ERROR: {:?}---", error);
+            Err(RequestError::Failed("The game failed to process the request"))
+        }
+    }
+}
+
+fn finish_game(user: ActorId, result: GameResult) {
+    let GameSessions { sessions, stats, .. } = game_sessions();
+    let session = sessions.get_mut(&user).expect("The session is not found");
+    stats.entry(user).or_default().record(&result, session.guess_count);
+    end_game(session, user);
+    session.game_status.game_result = Some(result.clone());
+    session.session_status = SessionStatus::GameEnded { result };
+}
+
+// Moves the finished game to the history and clears the session for a new round.
+fn archive_game(session: &mut Session) {
+    let SessionStatus::GameEnded { result } = session.session_status.clone() else {
+        return;
+    };
+    session.history.push(FinishedGame {
+        game_id: current_game_id(session),
+        result,
+        guesses: core::mem::take(&mut session.guesses),
+        rules: session.rules,
+        start_block: session.start_block,
+    });
+    // The timer of the finished game must not end whatever is played next.
+    session.game_id = None;
+    session.session_status = SessionStatus::Waiting;
+    session.game_status = GameStatus { game_result: None, attempts_remaining: 0, blocks_remaining: 0 };
+    session.msg_ids = None;
+    session.guess_count = 0;
+    session.solved_boards.clear();
+    session.keyboard = Keyboard::default();
+}
+
 #[no_mangle]
 extern "C" fn state() {
-    let session = unsafe { SESSION.as_ref().expect("State is not existing") };
-    msg::reply(session.clone(), 0).expect("Unable to get the state");
+    let query: StateQuery = msg::load().expect("Unable to decode `StateQuery`");
+    let game_sessions = unsafe { GAME_SESSIONS.as_ref().expect("State is not existing") };
+    let reply = match query {
+        StateQuery::Session(player) => {
+            StateReply::Session(game_sessions.sessions.get(&player).map(current_session))
+        }
+        StateQuery::Sessions => StateReply::Sessions(
+            game_sessions
+                .sessions
+                .iter()
+                .map(|(player, session)| (*player, current_session(session)))
+                .collect(),
+        ),
+        StateQuery::Stats(user) => StateReply::Stats(game_sessions.stats.get(&user).cloned()),
+        StateQuery::Relayers => StateReply::Relayers(game_sessions.relayers.iter().copied().collect()),
+        StateQuery::Rules => StateReply::Rules {
+            word_length: game_sessions.word_length,
+            rules: game_sessions.rules,
+            bounds: game_sessions.bounds,
+        },
+    };
+    msg::reply(reply, 0).expect("Unable to get the state");
 }
 
-fn get_game_status() -> GameStatus {
-    unsafe {
-        SESSION.as_ref().map(|s| {
-            s.game_status.clone()
-        }).expect("Game status is not initialized")
+// A copy of the session with the game status brought up to the current block.
+fn current_session(session: &Session) -> Session {
+    let mut session = session.clone();
+    game_status(&mut session);
+    session
+}
+
+// Updates the budgets left in the game status and returns it.
+fn game_status(session: &mut Session) -> GameStatus {
+    let (attempts_remaining, blocks_remaining) = if session.game_status.game_result.is_some() {
+        (0, 0)
+    } else {
+        let deadline = session.start_block.saturating_add(session.rules.timeout_blocks);
+        (
+            max_guesses(session).saturating_sub(session.guess_count),
+            deadline.saturating_sub(exec::block_height()),
+        )
+    };
+    session.game_status.attempts_remaining = attempts_remaining;
+    session.game_status.blocks_remaining = blocks_remaining;
+    session.game_status.clone()
+}
+
+fn game_sessions() -> &'static mut GameSessions {
+    unsafe { GAME_SESSIONS.as_mut().expect("The program is not initialized") }
+}
+
+fn session_mut(user: ActorId) -> &'static mut Session {
+    game_sessions().sessions.get_mut(&user).expect("The session is not found")
+}
+
+fn new_session(target_program_id: ActorId) -> Session {
+    Session {
+        target_program_id,
+        game_id: None,
+        session_status: SessionStatus::Waiting,
+        game_status: GameStatus {
+            game_result: None,
+            attempts_remaining: 0,
+            blocks_remaining: 0,
+        },
+        msg_ids: None,
+        guess_count: 0,
+        guesses: Vec::new(),
+        rules: Rules::default(),
+        solved_boards: Vec::new(),
+        keyboard: Keyboard::default(),
+        start_block: exec::block_height(),
+        history: Vec::new(),
+    }
+}
+
+fn no_game() -> SessionEvent {
+    SessionEvent::GameError("There is no game for this player, start a new one".into())
+}
+
+fn message_sent() -> SessionEvent {
+    SessionEvent::GameError("Message has already been sent, restart the game".into())
+}
+
+fn unexpected_reply() -> SessionEvent {
+    SessionEvent::GameError("Unexpected reply from the game".into())
+}
+
+// Games are numbered by their place in the history, counting from 1.
+fn current_game_id(session: &Session) -> u64 {
+    session.history.len() as u64 + 1
+}
+
+// Every extra board earns one extra guess: with 6 attempts that is 9 for four
+// boards and 13 for eight.
+fn max_guesses(session: &Session) -> u8 {
+    session
+        .rules
+        .max_attempts
+        .saturating_add(session.solved_boards.len().saturating_sub(1) as u8)
+}
+
+// Wordle only checks guesses of its own word length, so a guess with every
+// letter in place solves the board.
+fn is_solved(word: &str, feedback: &BoardFeedback) -> bool {
+    feedback.correct_positions.len() == word.chars().count()
+}
+
+// Lets wordle drop the finished game.
+fn end_game(session: &Session, user: ActorId) {
+    msg::send(session.target_program_id, Action::EndGame { user }, 0)
+        .expect("Error in sending a message");
+}
+
+fn error_message(error: &WordleError) -> String {
+    match error {
+        WordleError::InvalidAction => "The game could not decode the request".into(),
+        WordleError::InvalidWordLength { expected } => format!("The word must be {expected} letters long"),
+        WordleError::InvalidLetters => "The word contains letters outside of the game's alphabet".into(),
+        WordleError::GameNotFound => "There is no game for this user, start a new one".into(),
+        WordleError::GameOver => "The game is already over".into(),
+        WordleError::DailyNotConfigured => "The daily puzzle is not available yet".into(),
+        WordleError::DailyAlreadyPlayed { epoch } => {
+            format!("The daily puzzle of epoch {epoch} has already been played")
+        }
+        WordleError::DailyInProgress { epoch } => {
+            format!("The daily word of epoch {epoch} is revealed once the epoch is over")
+        }
+        WordleError::InvalidBoardCount => "The number of boards must be 2, 4 or 8".into(),
+        WordleError::InvalidEpochLength => "The epoch length must be positive".into(),
+        WordleError::InvalidGameTtl => "The game lifetime must be positive".into(),
+        WordleError::NotOwner => "Only the game owner can do this".into(),
+        WordleError::Unauthorized => "This session is not allowed to play on behalf of the user".into(),
+        WordleError::RoomNotFound => "There is no such race room".into(),
+        WordleError::RoomFull => "The race room is full".into(),
+        WordleError::AlreadyJoined => "The user has already joined the race room".into(),
+        WordleError::RaceAlreadyStarted => "The race has already started".into(),
+        WordleError::RaceInProgress => "The race word is revealed once the race is over".into(),
+        WordleError::ParticipantBusy { .. } => "A participant is playing another game".into(),
+        WordleError::NotHost => "Only the host can start the race".into(),
     }
 }
@@ -1,43 +1,80 @@
 #![no_std]
 
-use gtest::{Program, ProgramBuilder, System};
+use gstd::prelude::*;
+use gtest::{Log, Program, ProgramBuilder, RunResult, System, WasmProgram};
 use game_session_io::*;
+use wordle_io::{Action, BoardFeedback, Event, GameMode, Language, WordleError};
 
 const USER1: u64 = 10;
+const USER2: u64 = 11;
+const RELAYER: u64 = 12;
 const SESSION_PROGRAM_ID: u64 = 1;
 const TARGET_PROGRAM_ID: u64 = 2;
+// An account without a program, requests sent to it are never answered.
+const SILENT_TARGET: u64 = 20;
 
-#[test]
-fn test_game_session_state() {
-    let system = System::new();
-    system.init_logger();
+const WORDS: [&str; 3] = ["house", "human", "horse"];
 
+fn session_init(target_program_id: u64) -> SessionInit {
+    SessionInit {
+        target_program_id: target_program_id.into(),
+        word_length: 5,
+        rules: Rules::default(),
+        bounds: RuleBounds::default(),
+    }
+}
+
+fn init_programs(system: &System) -> Program<'_> {
     let proxy_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/game_session.opt.wasm")
         .with_id(SESSION_PROGRAM_ID)
-        .build(&system);
+        .build(system);
 
     let target_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/wordle.opt.wasm")
         .with_id(TARGET_PROGRAM_ID)
-        .build(&system);
+        .build(system);
 
     let init_target_program_result = target_program.send_bytes(USER1, []);
     assert!(!init_target_program_result.main_failed());
 
-    let init_proxy_program_result = proxy_program.send(USER1, target_program.id());
+    let add_proxy_result = target_program.send(USER1, Action::AddProxy { proxy: SESSION_PROGRAM_ID.into() });
+    assert!(!add_proxy_result.main_failed());
+
+    let init_proxy_program_result = proxy_program.send(USER1, session_init(TARGET_PROGRAM_ID));
     assert!(!init_proxy_program_result.main_failed());
 
-    let start_result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into() });
+    proxy_program
+}
+
+fn start_classic(program: &Program, user: u64) -> RunResult {
+    program.send(user, SessionAction::StartGame { user: user.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English, rules: None })
+}
+
+fn session_state(program: &Program, player: u64) -> Session {
+    let StateReply::Session(Some(session)) = program.read_state(StateQuery::Session(player.into())).unwrap() else {
+        panic!("The player must have a session");
+    };
+    session
+}
+
+#[test]
+fn test_game_session_state() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let start_result = start_classic(&proxy_program, USER1);
     assert!(!start_result.main_failed());
 
      proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "house".into() });
 
-     let state: Session = proxy_program.read_state(()).unwrap();
+     let state = session_state(&proxy_program, USER1);
      assert_eq!(state.session_status, SessionStatus::Waiting);
 
      proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "horse".into() });
      proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "human".into() });
 
-    let state: Session = proxy_program.read_state(()).unwrap();
+    let state = session_state(&proxy_program, USER1);
     assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Win });
 }
 
@@ -46,30 +83,670 @@ fn test_timeout() {
     let system = System::new();
     system.init_logger();
 
+    let proxy_program = init_programs(&system);
+
+    let start_result = start_classic(&proxy_program, USER1);
+    assert!(!start_result.main_failed());
+
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hello".into() });
+
+    // The player learns about the timeout without sending anything.
+    let game_over = system
+        .spend_blocks(200)
+        .iter()
+        .flat_map(|result| result.log())
+        .filter(|log| log.destination() == USER1.into())
+        .find_map(|log| match SessionEvent::decode(&mut log.payload()) {
+            Ok(SessionEvent::GameOver { result, word, .. }) => Some((result, word)),
+            _ => None,
+        })
+        .expect("The player must be told the game is over");
+    assert_eq!(game_over.0, GameResult::Lose);
+    assert!(game_over.1.is_some_and(|word| WORDS.contains(&word.as_str())));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+}
+
+#[test]
+fn test_daily_game_is_over_before_its_word_is_revealed() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+    system.get_program(TARGET_PROGRAM_ID).send(USER1, Action::SetDailyConfig { epoch_blocks: 1_000 });
+
+    proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English, rules: None });
+    let game_over = WORDS
+        .into_iter()
+        .find_map(|word| {
+            let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+            result.log().iter().find_map(|log| match SessionEvent::decode(&mut log.payload()) {
+                Ok(SessionEvent::GameOver { result, word, .. }) => Some((result, word)),
+                _ => None,
+            })
+        })
+        .expect("The daily game must be over");
+    // Other players are still guessing the same word.
+    assert_eq!(game_over, (GameResult::Win, None));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Win });
+}
+
+#[test]
+fn test_stale_timeouts_are_ignored() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+    let game_id = session_state(&proxy_program, USER1).game_id.expect("The game must be running");
+
+    let result = proxy_program.send(USER1, SessionAction::Timeout { user: USER1.into(), game_id });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("Only the session program can time games out".into()))));
+
+    for word in WORDS {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+    }
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::GameEnded { result: GameResult::Win });
+
+    // The timer of the won game still fires, but it changes nothing.
+    system.spend_blocks(200);
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Win });
+    let StateReply::Stats(Some(stats)) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+        panic!("The player must have stats");
+    };
+    assert_eq!((stats.games_played, stats.games_won), (1, 1));
+}
+
+#[test]
+fn test_timeout_of_a_finished_game_spares_a_failed_start() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+    for word in WORDS {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+    }
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::GameEnded { result: GameResult::Win });
+
+    let result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::MultiBoard { boards: 3 }, language: Language::English, rules: None });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The number of boards must be 2, 4 or 8".into()))));
+
+    // The timer of the won game finds no game of its own to end.
+    let results = system.spend_blocks(200);
+    assert!(!results.iter().flat_map(|result| result.log()).any(|log| {
+        log.destination() == USER1.into()
+            && matches!(SessionEvent::decode(&mut log.payload()), Ok(SessionEvent::GameOver { .. }))
+    }));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.game_id, None);
+    assert_eq!(state.history.len(), 1);
+    let StateReply::Stats(Some(stats)) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+        panic!("The player must have stats");
+    };
+    assert_eq!((stats.games_played, stats.games_won, stats.current_streak), (1, 1, 1));
+}
+
+#[test]
+fn test_hard_mode_violation_is_not_counted() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let start_result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: true, mode: GameMode::Classic, language: Language::English, rules: None });
+    assert!(!start_result.main_failed());
+
+    // Every word in the bank starts with "h", so it is revealed at position 0.
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hzzzz".into() });
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.guess_count, 1);
+
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!(state.guess_count, 1);
+}
+
+#[test]
+fn test_invalid_word_is_reported_and_not_counted() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let start_result = start_classic(&proxy_program, USER1);
+    assert!(!start_result.main_failed());
+
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hut".into() });
+    assert!(!result.main_failed());
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The word must be 5 letters long".into()))));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!(state.guess_count, 0);
+}
+
+#[test]
+fn test_multi_board_game_is_won_when_every_board_is_solved() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::MultiBoard { boards: 4 }, language: Language::English, rules: None });
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.solved_boards, vec![false; 4]);
+
+    // Every board hides one of the dictionary words.
+    for word in ["house", "horse", "human"] {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+    }
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.solved_boards, vec![true; 4]);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Win });
+}
+
+#[test]
+fn test_keyboard_tracks_guessed_letters() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+
+    // Every word in the bank starts with "h" and has no "z".
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hzzzz".into() });
+    let keyboard = Keyboard { letters: vec![("h".into(), LetterStatus::Correct), ("z".into(), LetterStatus::Absent)] };
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::WordChecked {
+        user: USER1.into(),
+        correct_positions: vec![0],
+        contained_in_word: vec![],
+        keyboard: keyboard.clone(),
+    })));
+
+    // A later miss does not downgrade a known letter.
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzh".into() });
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.keyboard.status('h'), Some(LetterStatus::Correct));
+    assert_eq!(state.keyboard.status('z'), Some(LetterStatus::Absent));
+    assert_eq!(state.keyboard.status('a'), None);
+}
+
+#[test]
+fn test_word_is_revealed_when_the_game_is_lost() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+
+    for _ in 0..5 {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    }
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    let game_over = result
+        .log()
+        .iter()
+        .find_map(|log| match SessionEvent::decode(&mut log.payload()) {
+            Ok(SessionEvent::GameOver { result, word, guesses }) => Some((result, word, guesses)),
+            _ => None,
+        })
+        .expect("The game must be over");
+    assert_eq!(game_over.0, GameResult::Lose);
+    assert!(game_over.1.is_some_and(|word| WORDS.contains(&word.as_str())));
+    assert_eq!(game_over.2, 6);
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+}
+
+#[test]
+fn test_stats_are_kept_per_player() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let StateReply::Stats(stats) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+        panic!("The state must reply with the stats");
+    };
+    assert_eq!(stats, None);
+
+    start_classic(&proxy_program, USER1);
+    for _ in 0..6 {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    }
+
+    let StateReply::Stats(Some(stats)) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+        panic!("The player must have stats");
+    };
+    assert_eq!(stats, PlayerStats { games_played: 1, ..Default::default() });
+    assert_eq!(stats.win_percentage(), 0);
+}
+
+#[test]
+fn test_stats_track_wins_streaks_and_guess_distribution() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+    let start = || start_classic(&proxy_program, USER1);
+    let stats = || {
+        let StateReply::Stats(Some(stats)) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+            panic!("The player must have stats");
+        };
+        stats
+    };
+
+    // Every dictionary word is tried until the game is won.
+    let mut distribution = Vec::new();
+    for game in 1..=2 {
+        start();
+        for word in WORDS {
+            proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+            if session_state(&proxy_program, USER1).session_status != SessionStatus::Waiting {
+                break;
+            }
+        }
+        let state = session_state(&proxy_program, USER1);
+        assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Win });
+        let guesses = state.guess_count as usize;
+        if distribution.len() < guesses {
+            distribution.resize(guesses, 0);
+        }
+        distribution[guesses - 1] += 1;
+
+        assert_eq!(stats(), PlayerStats {
+            games_played: game,
+            games_won: game,
+            current_streak: game,
+            max_streak: game,
+            guess_distribution: distribution.clone(),
+        });
+        assert_eq!(stats().win_percentage(), 100);
+    }
+
+    start();
+    for _ in 0..6 {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    }
+    assert_eq!(stats(), PlayerStats {
+        games_played: 3,
+        games_won: 2,
+        current_streak: 0,
+        max_streak: 2,
+        guess_distribution: distribution,
+    });
+    assert_eq!(stats().win_percentage(), 66);
+}
+
+#[test]
+fn test_players_have_separate_sessions() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let StateReply::Session(session) = proxy_program.read_state(StateQuery::Session(USER1.into())).unwrap() else {
+        panic!("The state must reply with the session");
+    };
+    assert!(session.is_none());
+
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "house".into() });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("There is no game for this player, start a new one".into()))));
+
+    start_classic(&proxy_program, USER1);
+    start_classic(&proxy_program, USER2);
+
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    proxy_program.send(USER2, SessionAction::CheckWord { user: USER2.into(), word: "zzzzz".into() });
+
+    assert_eq!(session_state(&proxy_program, USER1).guess_count, 2);
+    assert_eq!(session_state(&proxy_program, USER2).guess_count, 1);
+
+    let StateReply::Sessions(sessions) = proxy_program.read_state(StateQuery::Sessions).unwrap() else {
+        panic!("The state must reply with the sessions");
+    };
+    assert_eq!(sessions.len(), 2);
+}
+
+#[test]
+fn test_reply_wakes_the_waiting_message() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+    start_classic(&proxy_program, USER2);
+
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::WordChecked {
+        user: USER1.into(),
+        correct_positions: vec![],
+        contained_in_word: vec![],
+        keyboard: Keyboard { letters: vec![("z".into(), LetterStatus::Absent)] },
+    })));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!(state.msg_ids.map(|(_, original)| original), Some(result.sent_message_id()));
+
+    // The other player's session is untouched by the reply.
+    let state = session_state(&proxy_program, USER2);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!(state.guess_count, 0);
+}
+
+fn init_silent_session(system: &System) -> Program<'_> {
     let proxy_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/game_session.opt.wasm")
         .with_id(SESSION_PROGRAM_ID)
-        .build(&system);
+        .build(system);
 
-    let target_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/wordle.opt.wasm")
-        .with_id(TARGET_PROGRAM_ID)
+    let init_proxy_program_result = proxy_program.send(USER1, session_init(SILENT_TARGET));
+    assert!(!init_proxy_program_result.main_failed());
+
+    proxy_program
+}
+
+#[test]
+fn test_restart_abandons_a_stuck_round() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_silent_session(&system);
+
+    start_classic(&proxy_program, USER1);
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::MessageSent);
+
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "house".into() });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("Message has already been sent, restart the game".into()))));
+
+    let result = proxy_program.send(RELAYER, SessionAction::Restart { user: USER1.into() });
+    assert!(result.contains(&Log::builder().dest(RELAYER).payload(SessionEvent::GameError("The sender may not act on behalf of this player".into()))));
+
+    // A relayer may restart the round for the player.
+    proxy_program.send(USER1, SessionAction::AddRelayer { relayer: RELAYER.into() });
+    let result = proxy_program.send(RELAYER, SessionAction::Restart { user: USER1.into() });
+    assert!(result.contains(&Log::builder().dest(RELAYER).payload(SessionEvent::Restarted)));
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::Waiting);
+    assert_eq!(session_state(&proxy_program, USER1).msg_ids, None);
+
+    let result = proxy_program.send(USER1, SessionAction::Restart { user: USER1.into() });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("There is no request to restart".into()))));
+}
+
+#[test]
+fn test_unanswered_request_times_out() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_silent_session(&system);
+
+    start_classic(&proxy_program, USER1);
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::MessageSent);
+
+    system.spend_blocks(10);
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::Waiting);
+}
+
+#[test]
+fn test_players_act_only_for_themselves_unless_relayed() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let result = proxy_program.send(USER2, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English, rules: None });
+    assert!(result.contains(&Log::builder().dest(USER2).payload(SessionEvent::GameError("The sender may not act on behalf of this player".into()))));
+
+    let result = proxy_program.send(USER2, SessionAction::AddRelayer { relayer: RELAYER.into() });
+    assert!(result.contains(&Log::builder().dest(USER2).payload(SessionEvent::GameError("Only the owner can manage relayers".into()))));
+
+    let result = proxy_program.send(USER1, SessionAction::AddRelayer { relayer: RELAYER.into() });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::RelayerAdded { relayer: RELAYER.into() })));
+
+    proxy_program.send(RELAYER, SessionAction::StartGame { user: USER2.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English, rules: None });
+    proxy_program.send(RELAYER, SessionAction::CheckWord { user: USER2.into(), word: "zzzzz".into() });
+    assert_eq!(session_state(&proxy_program, USER2).guess_count, 1);
+
+    let StateReply::Session(session) = proxy_program.read_state(StateQuery::Session(RELAYER.into())).unwrap() else {
+        panic!("The state must reply with the session");
+    };
+    assert!(session.is_none());
+}
+
+// Starts games, but fails on every guess and garbles every other reply.
+#[derive(Debug)]
+struct FailingWordle;
+
+impl WasmProgram for FailingWordle {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        match Action::decode(&mut payload.as_slice()) {
+            Ok(Action::StartGame { user, .. }) => {
+                let reply: Result<Event, WordleError> = Ok(Event::GameStarted { user, commitments: vec![[0; 32]] });
+                Ok(Some(reply.encode()))
+            }
+            Ok(Action::CheckWord { .. }) => Err("The guess cannot be checked"),
+            _ => Ok(Some(vec![0xff])),
+        }
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn test_failed_replies_are_reported_and_not_counted() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/game_session.opt.wasm")
+        .with_id(SESSION_PROGRAM_ID)
         .build(&system);
+    let target_program = Program::mock_with_id(&system, TARGET_PROGRAM_ID, FailingWordle);
+    assert!(!target_program.send_bytes(USER1, []).main_failed());
+    assert!(!proxy_program.send(USER1, session_init(TARGET_PROGRAM_ID)).main_failed());
 
-    let init_target_program_result = target_program.send_bytes(USER1, []);
-    assert!(!init_target_program_result.main_failed());
+    start_classic(&proxy_program, USER1);
 
-    let init_proxy_program_result = proxy_program.send(USER1, target_program.id());
-    assert!(!init_proxy_program_result.main_failed());
+    let result = proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "house".into() });
+    assert!(!result.main_failed());
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The game failed to process the request".into()))));
 
-    let start_result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into() });
-    assert!(!start_result.main_failed());
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!(state.guess_count, 0);
 
-    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hello".into() });
+    // The word cannot be revealed from a garbled reply, but the game still ends.
+    let results = system.spend_blocks(200);
+    let game_over = Log::builder().dest(USER1).payload(SessionEvent::GameOver { result: GameResult::Lose, word: None, guesses: 0 });
+    assert!(results.iter().any(|result| result.contains(&game_over)));
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+}
 
-    system.spend_blocks(200);
+#[test]
+fn test_games_can_be_started_with_their_own_rules() {
+    let system = System::new();
+    system.init_logger();
 
-    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hello".into() });
-    assert!(!start_result.main_failed());
+    let proxy_program = init_programs(&system);
+
+    let rules = Rules { max_attempts: 2, timeout_blocks: 100 };
+    let result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English, rules: Some(Rules { max_attempts: 20, ..rules }) });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The rules are out of the owner's bounds".into()))));
+
+    proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English, rules: Some(rules) });
+    assert_eq!(session_state(&proxy_program, USER1).rules, rules);
+
+    // A game in progress keeps its word and rules.
+    let result = start_classic(&proxy_program, USER1);
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("A game is already in progress, finish it first".into()))));
+
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+
+    // The rules of a game that wordle refuses to start are not applied.
+    let result = proxy_program.send(USER1, SessionAction::StartGame { user: USER1.into(), hard_mode: false, mode: GameMode::MultiBoard { boards: 3 }, language: Language::English, rules: Some(Rules { max_attempts: 5, ..rules }) });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The number of boards must be 2, 4 or 8".into()))));
+    assert_eq!(session_state(&proxy_program, USER1).rules, rules);
+}
+
+#[test]
+fn test_owner_sets_the_default_rules() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    let rules = Rules { max_attempts: 3, timeout_blocks: 50 };
+    let bounds = RuleBounds { min_attempts: 3, max_attempts: 3, min_timeout_blocks: 50, max_timeout_blocks: 50 };
+
+    let result = proxy_program.send(USER2, SessionAction::SetRules { rules, bounds });
+    assert!(result.contains(&Log::builder().dest(USER2).payload(SessionEvent::GameError("Only the owner can set the rules".into()))));
+
+    let result = proxy_program.send(USER1, SessionAction::SetRules { rules: Rules::default(), bounds });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("The default rules are out of bounds".into()))));
+
+    let result = proxy_program.send(USER1, SessionAction::SetRules { rules, bounds });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::RulesSet { rules, bounds })));
+
+    let StateReply::Rules { word_length, rules: state_rules, bounds: state_bounds } = proxy_program.read_state(StateQuery::Rules).unwrap() else {
+        panic!("The state must reply with the rules");
+    };
+    assert_eq!((word_length, state_rules, state_bounds), (5, rules, bounds));
+
+    start_classic(&proxy_program, USER2);
+    assert_eq!(session_state(&proxy_program, USER2).rules, rules);
+
+    let inverted = RuleBounds { min_attempts: 4, ..bounds };
+    let result = proxy_program.send(USER1, SessionAction::SetRules { rules, bounds: inverted });
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameError("A minimum of the bounds is above its maximum".into()))));
+}
+
+#[test]
+fn test_state_has_guess_history_and_remaining_budget() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+    let start_block = session_state(&proxy_program, USER1).start_block;
+
+    system.spend_blocks(5);
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hzzzz".into() });
+    // Rejected guesses are not part of the history.
+    proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "hut".into() });
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.guesses.len(), 1);
+    assert_eq!(state.guesses[0].word, "hzzzz");
+    assert_eq!(state.guesses[0].boards, vec![BoardFeedback { correct_positions: vec![0], contained_in_word: vec![] }]);
+    assert!(state.guesses[0].block > start_block);
+
+    assert_eq!(state.game_status.attempts_remaining, 5);
+    assert_eq!(state.game_status.blocks_remaining, start_block + 200 - system.block_height());
+}
+
+#[test]
+fn test_finished_game_is_archived_when_a_new_one_starts() {
+    let system = System::new();
+    system.init_logger();
+
+    let proxy_program = init_programs(&system);
+
+    start_classic(&proxy_program, USER1);
+    for word in WORDS {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+    }
+    let first_game = session_state(&proxy_program, USER1);
+    assert_eq!(first_game.session_status, SessionStatus::GameEnded { result: GameResult::Win });
+    assert_eq!(first_game.game_status.game_result, Some(GameResult::Win));
+
+    system.spend_blocks(5);
+    let result = start_classic(&proxy_program, USER1);
+    assert!(result.contains(&Log::builder().dest(USER1).payload(SessionEvent::GameStarted { user: USER1.into() })));
+
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::Waiting);
+    assert_eq!((first_game.game_id, state.game_id), (Some(1), Some(2)));
+    assert_eq!(state.game_status.game_result, None);
+    assert_eq!(state.guess_count, 0);
+    assert!(state.guesses.is_empty());
+    assert!(state.start_block > first_game.start_block);
+    assert_eq!(state.history, vec![FinishedGame {
+        game_id: 1,
+        result: GameResult::Win,
+        guesses: first_game.guesses,
+        rules: Rules::default(),
+        start_block: first_game.start_block,
+    }]);
+
+    for _ in 0..6 {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: "zzzzz".into() });
+    }
+    let state = session_state(&proxy_program, USER1);
+    assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+    assert_eq!(state.guess_count, 6);
+
+    let StateReply::Stats(Some(stats)) = proxy_program.read_state(StateQuery::Stats(USER1.into())).unwrap() else {
+        panic!("The player must have stats");
+    };
+    assert_eq!((stats.games_played, stats.games_won), (2, 1));
+}
+
+#[test]
+fn test_win_does_not_depend_on_the_configured_word_length() {
+    let system = System::new();
+    system.init_logger();
+
+    let broken_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/game_session.opt.wasm")
+        .with_id(3)
+        .build(&system);
+    assert!(broken_program.send(USER1, SessionInit { word_length: 0, ..session_init(TARGET_PROGRAM_ID) }).main_failed());
+
+    let proxy_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/game_session.opt.wasm")
+        .with_id(SESSION_PROGRAM_ID)
+        .build(&system);
+    let target_program: Program = ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/wordle.opt.wasm")
+        .with_id(TARGET_PROGRAM_ID)
+        .build(&system);
+    assert!(!target_program.send_bytes(USER1, []).main_failed());
+    assert!(!target_program.send(USER1, Action::AddProxy { proxy: SESSION_PROGRAM_ID.into() }).main_failed());
+    assert!(!proxy_program.send(USER1, SessionInit { word_length: 6, ..session_init(TARGET_PROGRAM_ID) }).main_failed());
 
-     let state: Session = proxy_program.read_state(()).unwrap();
-     assert_eq!(state.session_status, SessionStatus::GameEnded { result: GameResult::Lose });
+    start_classic(&proxy_program, USER1);
+    for word in WORDS {
+        proxy_program.send(USER1, SessionAction::CheckWord { user: USER1.into(), word: word.into() });
+    }
+    assert_eq!(session_state(&proxy_program, USER1).session_status, SessionStatus::GameEnded { result: GameResult::Win });
 }
@@ -2,32 +2,282 @@
 
 use gmeta::{InOut, Metadata};
 use gstd::{prelude::*, ActorId};
+use sha2::{Digest, Sha256};
 
 pub struct WordleMetadata;
 
 impl Metadata for WordleMetadata {
     type Init = ();
-    type Handle = InOut<Action, Event>;
+    type Handle = InOut<Action, Result<Event, WordleError>>;
     type Others = ();
     type Reply = ();
     type Signal = ();
-    type State = ();
+    type State = InOut<StateQuery, StateReply>;
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum Action {
-    StartGame { user: ActorId },
+    StartGame {
+        user: ActorId,
+        hard_mode: bool,
+        mode: GameMode,
+        language: Language,
+    },
     CheckWord { user: ActorId, word: String },
+    /// Ends the game and reveals the hidden word together with its commitment salt.
+    Reveal { user: ActorId },
+    /// Removes the game once the session is done with it.
+    EndGame { user: ActorId },
+    /// Sent by the program to itself to remove idle games.
+    ExpireGame { user: ActorId, game_id: u64 },
+    /// Owner only: sets the number of idle blocks after which a game expires.
+    SetGameTtl { blocks: u32 },
+    /// Creates a race room hosted by `user`.
+    CreateRoom { user: ActorId, language: Language },
+    JoinRoom { user: ActorId, room_id: u64 },
+    /// Host only: gives every participant a game with the same hidden word.
+    StartRace { user: ActorId, room_id: u64 },
+    /// Sent by the program to itself to pick the winner of a race, once someone
+    /// solves it or when its deadline is reached.
+    FinishRace { room_id: u64 },
+    /// Owner only: allows a program to act on behalf of any user.
+    AddProxy { proxy: ActorId },
+    /// Owner only.
+    RemoveProxy { proxy: ActorId },
+    /// Owner only: sets the epoch length of the daily puzzle. Changing it starts
+    /// a new epoch at once.
+    SetDailyConfig { epoch_blocks: u32 },
+    DailyStats { epoch: u32 },
+}
+
+#[derive(Debug, Default, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum GameMode {
+    /// A word picked at random for this player.
+    #[default]
+    Classic,
+    /// The puzzle shared by everyone in the current epoch, playable once per epoch.
+    /// Its word is drawn when the epoch is first played and can only be revealed
+    /// once the epoch is over.
+    Daily,
+    /// Every guess is scored against 2, 4 or 8 hidden words at once.
+    MultiBoard { boards: u8 },
+    /// No word is fixed: every guess gets the feedback that keeps the most
+    /// answers possible, until a single one is left and guessed.
+    Adversarial,
+}
+
+#[derive(Debug, Default, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    Russian,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
 pub enum Event {
     GameStarted {
         user: ActorId,
+        /// Commitments to the hidden words, one per board, see [`word_commitment`].
+        /// Empty for adversarial games, which have no word to commit to.
+        commitments: Vec<[u8; 32]>,
     },
     WordChecked {
         user: ActorId,
         correct_positions: Vec<u8>,
         contained_in_word: Vec<u8>,
     },
+    /// Reply to a guess in a multi-board game, with the feedback of every board.
+    BoardsChecked {
+        user: ActorId,
+        boards: Vec<BoardFeedback>,
+    },
+    HardModeViolation {
+        user: ActorId,
+        violation: HardModeViolation,
+    },
+    WordRevealed {
+        user: ActorId,
+        /// The hidden words, or the answers still possible in an adversarial game.
+        words: Vec<String>,
+        salts: Vec<[u8; 32]>,
+    },
+    GameEnded {
+        user: ActorId,
+    },
+    GameExpired {
+        user: ActorId,
+    },
+    ExpiryPostponed {
+        user: ActorId,
+        blocks: u32,
+    },
+    GameTtlSet {
+        blocks: u32,
+    },
+    RoomCreated {
+        room_id: u64,
+        host: ActorId,
+    },
+    PlayerJoined {
+        room_id: u64,
+        player: ActorId,
+    },
+    RaceStarted {
+        room_id: u64,
+        commitment: [u8; 32],
+    },
+    /// `standings` are ordered by solve block, then by guess count. There is no
+    /// `winner` when nobody solved the word before the deadline.
+    RaceFinished {
+        room_id: u64,
+        winner: Option<ActorId>,
+        standings: Vec<RaceResult>,
+    },
+    ProxyAdded {
+        proxy: ActorId,
+    },
+    ProxyRemoved {
+        proxy: ActorId,
+    },
+    DailyConfigSet {
+        epoch_blocks: u32,
+    },
+    /// `distribution[i]` is the number of players who solved the puzzle in `i + 1` guesses.
+    DailyStats {
+        epoch: u32,
+        players: u32,
+        distribution: Vec<u32>,
+    },
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct BoardFeedback {
+    pub correct_positions: Vec<u8>,
+    pub contained_in_word: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct Guess {
+    pub word: String,
+    /// Feedback for each board of the game.
+    pub boards: Vec<BoardFeedback>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub enum WordleError {
+    /// The payload could not be decoded as an [`Action`].
+    InvalidAction,
+    InvalidWordLength { expected: u8 },
+    /// The word has letters outside of the game language's alphabet.
+    InvalidLetters,
+    GameNotFound,
+    GameOver,
+    DailyNotConfigured,
+    DailyAlreadyPlayed { epoch: u32 },
+    /// The daily word cannot be revealed while its epoch is still played.
+    DailyInProgress { epoch: u32 },
+    InvalidBoardCount,
+    InvalidEpochLength,
+    InvalidGameTtl,
+    NotOwner,
+    /// The sender is neither the user nor an allowed proxy, or the game was
+    /// started through another sender.
+    Unauthorized,
+    RoomNotFound,
+    RoomFull,
+    AlreadyJoined,
+    RaceAlreadyStarted,
+    /// The race word cannot be revealed before the winner is picked.
+    RaceInProgress,
+    /// A participant is playing a game started through another sender.
+    ParticipantBusy { player: ActorId },
+    NotHost,
+}
+
+/// Reason a guess was rejected in hard mode.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub enum HardModeViolation {
+    /// A letter revealed at its correct position was moved or dropped.
+    CorrectLetterMoved { position: u8, letter: String },
+    /// A letter revealed as contained in the word is not used in the guess.
+    ContainedLetterMissing { letter: String },
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct RaceResult {
+    pub player: ActorId,
+    pub guesses: u32,
+    pub block: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateQuery {
+    All,
+    Game(ActorId),
+    Room(u64),
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub enum StateReply {
+    All(WordleState),
+    Game(Option<GameInfo>),
+    Room(Option<RoomInfo>),
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct WordleState {
+    pub owner: ActorId,
+    pub proxies: Vec<ActorId>,
+    /// Games kept in storage, including finished ones not yet ended or expired.
+    pub live_games: u32,
+    /// Games that are still being played.
+    pub active_games: u32,
+    pub games_started: u64,
+    pub game_ttl: u32,
+    pub games: Vec<(ActorId, GameInfo)>,
+    /// Number of words in each language's dictionary.
+    pub dictionaries: Vec<(Language, u32)>,
+    pub daily_epoch_blocks: Option<u32>,
+}
+
+/// A player's game as returned by the state: the hidden word is only given as
+/// its commitment.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct GameInfo {
+    pub start_block: u32,
+    pub last_active_block: u32,
+    pub commitments: Vec<[u8; 32]>,
+    pub solved_boards: Vec<bool>,
+    /// Number of answers still possible in an adversarial game.
+    pub candidates_left: Option<u32>,
+    pub hard_mode: bool,
+    pub language: Language,
+    pub daily_epoch: Option<u32>,
+    pub room_id: Option<u64>,
+    pub guesses: Vec<Guess>,
+    pub finished: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq)]
+pub struct RoomInfo {
+    pub host: ActorId,
+    pub language: Language,
+    pub participants: Vec<ActorId>,
+    pub started: bool,
+    pub standings: Vec<RaceResult>,
+}
+
+/// Computes the commitment to a hidden word, so that a revealed word can be
+/// checked against the commitment published in [`Event::GameStarted`].
+///
+/// The commitment proves the word was fixed when the game started, it does not
+/// keep the word secret: the word itself is kept in the program memory, and the
+/// salt is derived on chain from public inputs while the dictionaries are small,
+/// so the word can also be found by trying every entry.
+pub fn word_commitment(word: &str, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(word.as_bytes());
+    hasher.finalize().into()
 }
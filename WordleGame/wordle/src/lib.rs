@@ -1,74 +1,889 @@
 #![no_std]
-use gstd::{collections::HashMap, exec, msg, prelude::*, ActorId};
+use gstd::{
+    collections::{HashMap, HashSet},
+    exec, msg,
+    prelude::*,
+    ActorId,
+};
+use sha2::{Digest, Sha256};
 use wordle_io::*;
 
 static mut WORDLE: Option<Wordle> = None;
 
+const WORD_LENGTH: usize = 5;
+const DEFAULT_GAME_TTL: u32 = 1_000;
+const MULTI_BOARD_COUNTS: [u8; 3] = [2, 4, 8];
+// Number of daily epochs whose results are kept for `DailyStats`.
+const DAILY_EPOCHS_KEPT: u32 = 30;
+// Bounds the number of messages a race broadcast sends.
+const MAX_RACE_PARTICIPANTS: usize = 16;
+// Blocks after which a race nobody has solved is finished without a winner.
+const RACE_DURATION: u32 = 100;
+// Gas left for the rest of `CheckWord` when an adversarial game stops scoring candidates.
+const PARTITION_GAS_RESERVE: u64 = 1_000_000_000;
+// Feedback pattern of a guess that matches the word, see `feedback_pattern`.
+const SOLVED_PATTERN: usize = (1 << WORD_LENGTH) - 1;
+
 // TODO: change back
-const BANK_OF_WORDS: [&str; 3] = ["house", "human", "horse"];
+const ENGLISH_WORDS: [&str; 3] = ["house", "human", "horse"];
+const SPANISH_WORDS: [&str; 3] = ["señor", "sueño", "dueño"];
+const RUSSIAN_WORDS: [&str; 3] = ["книга", "школа", "слово"];
+
+const LANGUAGES: [Language; 3] = [Language::English, Language::Spanish, Language::Russian];
+
+fn dictionary(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::English => &ENGLISH_WORDS,
+        Language::Spanish => &SPANISH_WORDS,
+        Language::Russian => &RUSSIAN_WORDS,
+    }
+}
+
+fn alphabet(language: Language) -> &'static str {
+    match language {
+        Language::English => "abcdefghijklmnopqrstuvwxyz",
+        Language::Spanish => "abcdefghijklmnñopqrstuvwxyzáéíóúü",
+        Language::Russian => "абвгдеёжзийклмнопрстуфхцчшщъыьэюя",
+    }
+}
 
 #[derive(Default)]
 struct Wordle {
-    games: HashMap<ActorId, String>,
+    owner: ActorId,
+    proxies: HashSet<ActorId>,
+    games: HashMap<ActorId, Game>,
+    games_started: u64,
+    // Number of blocks without a guess after which a game is removed.
+    game_ttl: u32,
+    daily_config: Option<DailyConfig>,
+    daily_epochs: HashMap<u32, DailyEpoch>,
+    rooms: HashMap<u64, Room>,
+    rooms_created: u64,
+}
+
+struct Game {
+    id: u64,
+    // The user or the proxy that started the game, only it may play the game.
+    caller: ActorId,
+    start_block: u32,
+    last_active_block: u32,
+    // One board per hidden word, every guess is scored against all of them.
+    boards: Vec<Board>,
+    // Set instead of boards in adversarial games.
+    candidates: Option<Candidates>,
+    hard_mode: bool,
+    language: Language,
+    guesses: Vec<Guess>,
+    daily_epoch: Option<u32>,
+    room_id: Option<u64>,
+    finished: bool,
+}
+
+// A hidden word, only its commitment is given out until it is revealed.
+struct Board {
+    salt: [u8; 32],
+    commitment: [u8; 32],
+    word: String,
+    solved: bool,
+}
+
+impl Board {
+    fn new(word: &str, salt: [u8; 32]) -> Self {
+        Self {
+            salt,
+            commitment: word_commitment(word, &salt),
+            word: word.into(),
+            solved: false,
+        }
+    }
+}
+
+// Answers still possible in an adversarial game, one bit per dictionary index.
+struct Candidates {
+    bits: Vec<u64>,
+}
+
+impl Candidates {
+    fn full(len: usize) -> Self {
+        let mut candidates = Self { bits: Vec::new() };
+        (0..len).for_each(|index| candidates.insert(index));
+        candidates
+    }
+
+    fn insert(&mut self, index: usize) {
+        let (word, bit) = (index / 64, index % 64);
+        if self.bits.len() <= word {
+            self.bits.resize(word + 1, 0);
+        }
+        self.bits[word] |= 1 << bit;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word, bits)| {
+            (0..64)
+                .filter(move |bit| bits & (1 << bit) != 0)
+                .map(move |bit| word * 64 + bit)
+        })
+    }
+
+    fn len(&self) -> u32 {
+        self.bits.iter().map(|bits| bits.count_ones()).sum()
+    }
+
+    // Splits the candidates by the feedback they would give to `word`, keeps the
+    // largest group and returns its feedback. Candidates are only scored while
+    // gas lasts, the ones left unscored are dropped.
+    fn narrow(&mut self, words: &[&str], word: &str) -> BoardFeedback {
+        let mut counts = vec![0u32; 1 << (2 * WORD_LENGTH)];
+        let mut scored = Vec::new();
+        for index in self.iter() {
+            if !scored.is_empty() && exec::gas_available() < PARTITION_GAS_RESERVE {
+                break;
+            }
+            let pattern = feedback_pattern(&check_against(words[index], word));
+            counts[pattern] += 1;
+            scored.push((index, pattern));
+        }
+
+        // Ties are resolved against solving the game.
+        let mut best = scored.first().map(|(_, pattern)| *pattern).expect("No candidates are left");
+        for (pattern, count) in counts.iter().enumerate() {
+            if *count > counts[best] || (*count == counts[best] && best == SOLVED_PATTERN) {
+                best = pattern;
+            }
+        }
+
+        self.bits.clear();
+        for (index, _) in scored.iter().filter(|(_, pattern)| *pattern == best) {
+            self.insert(*index);
+        }
+        let representative = self.iter().next().expect("The largest group is not empty");
+        check_against(words[representative], word)
+    }
+}
+
+// Epochs are counted from the block the config was set at, continuing the
+// numbering of the previous config so played epochs are never reused.
+struct DailyConfig {
+    epoch_blocks: u32,
+    start_block: u32,
+    first_epoch: u32,
+}
+
+#[derive(Default)]
+struct DailyEpoch {
+    players: HashSet<ActorId>,
+    distribution: Vec<u32>,
+    // Drawn when the epoch is first played in a language, so nothing on chain
+    // tells the word of a later epoch in advance.
+    words: HashMap<Language, Board>,
+}
+
+struct Room {
+    host: ActorId,
+    language: Language,
+    participants: Vec<ActorId>,
+    // The sender each participant joined through, their race game is played through it.
+    callers: HashMap<ActorId, ActorId>,
+    started: bool,
+    standings: Vec<RaceResult>,
+}
+
+impl Room {
+    fn info(&self) -> RoomInfo {
+        RoomInfo {
+            host: self.host,
+            language: self.language,
+            participants: self.participants.clone(),
+            started: self.started,
+            standings: self.standings.clone(),
+        }
+    }
+}
+
+impl Game {
+    fn new(words: &[&str], hard_mode: bool, language: Language) -> Self {
+        let salt = get_random_salt();
+        let block = exec::block_height();
+        Self {
+            id: 0,
+            caller: msg::source(),
+            start_block: block,
+            last_active_block: block,
+            boards: words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| Board::new(word, get_board_salt(&salt, i)))
+                .collect(),
+            candidates: None,
+            hard_mode,
+            language,
+            guesses: Vec::new(),
+            daily_epoch: None,
+            room_id: None,
+            finished: false,
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        match self.candidates {
+            // The adversary gives in only when a single answer is left and it is guessed.
+            Some(_) => self
+                .guesses
+                .last()
+                .is_some_and(|guess| guess.boards[0].correct_positions.len() == WORD_LENGTH),
+            None => self.boards.iter().all(|board| board.solved),
+        }
+    }
+
+    // Keeps a player from guessing directly on a game a proxy counts the guesses of.
+    fn check_caller(&self) -> Result<(), WordleError> {
+        if msg::source() == self.caller {
+            Ok(())
+        } else {
+            Err(WordleError::Unauthorized)
+        }
+    }
+
+    fn commitments(&self) -> Vec<[u8; 32]> {
+        self.boards.iter().map(|board| board.commitment).collect()
+    }
+
+    fn info(&self) -> GameInfo {
+        GameInfo {
+            start_block: self.start_block,
+            last_active_block: self.last_active_block,
+            commitments: self.commitments(),
+            solved_boards: self.boards.iter().map(|board| board.solved).collect(),
+            candidates_left: self.candidates.as_ref().map(Candidates::len),
+            hard_mode: self.hard_mode,
+            language: self.language,
+            daily_epoch: self.daily_epoch,
+            room_id: self.room_id,
+            guesses: self.guesses.clone(),
+            finished: self.finished,
+        }
+    }
+
+    fn check_hard_mode(&self, word: &str) -> Result<(), HardModeViolation> {
+        if !self.hard_mode {
+            return Ok(());
+        }
+        // Hints from solved boards no longer constrain the guesses. Adversarial
+        // games have no boards, so their hints always apply.
+        let boards = &self.boards;
+        let hints = self.guesses.iter().flat_map(move |guess| {
+            guess
+                .boards
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !boards.get(*i).is_some_and(|board| board.solved))
+                .map(move |(_, feedback)| (&guess.word, feedback))
+        });
+        for (guess, feedback) in hints {
+            for &position in &feedback.correct_positions {
+                let letter = guess
+                    .chars()
+                    .nth(position as usize)
+                    .expect("Feedback refers to a missing letter");
+                if word.chars().nth(position as usize) != Some(letter) {
+                    return Err(HardModeViolation::CorrectLetterMoved {
+                        position,
+                        letter: letter.to_string(),
+                    });
+                }
+            }
+            for &position in &feedback.contained_in_word {
+                let letter = guess
+                    .chars()
+                    .nth(position as usize)
+                    .expect("Feedback refers to a missing letter");
+                if !word.contains(letter) {
+                    return Err(HardModeViolation::ContainedLetterMissing {
+                        letter: letter.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Wordle {
+    // Users play directly, while proxies such as `game-session` act on their behalf.
+    fn authorize(&self, user: ActorId) -> Result<(), WordleError> {
+        let source = msg::source();
+        if source == user || self.proxies.contains(&source) {
+            Ok(())
+        } else {
+            Err(WordleError::Unauthorized)
+        }
+    }
+
+    fn check_owner(&self) -> Result<(), WordleError> {
+        if msg::source() == self.owner {
+            Ok(())
+        } else {
+            Err(WordleError::NotOwner)
+        }
+    }
+
+    fn start_game(
+        &mut self,
+        user: ActorId,
+        hard_mode: bool,
+        mode: GameMode,
+        language: Language,
+    ) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        if let Some(game) = self.games.get(&user).filter(|game| !game.finished) {
+            game.check_caller()?;
+        }
+        let words = dictionary(language);
+        let daily_word;
+        let (hidden_words, daily_epoch) = match mode {
+            GameMode::Classic => {
+                let random_id = get_random_value(user, words.len() as u64);
+                (vec![words[random_id as usize]], None)
+            }
+            GameMode::MultiBoard { boards } => {
+                if !MULTI_BOARD_COUNTS.contains(&boards) {
+                    return Err(WordleError::InvalidBoardCount);
+                }
+                // Boards are drawn independently, a small dictionary may repeat a word.
+                let hidden_words = (0..boards)
+                    .map(|_| words[get_random_value(user, words.len() as u64) as usize])
+                    .collect();
+                (hidden_words, None)
+            }
+            GameMode::Daily => {
+                let epoch = self.current_epoch().ok_or(WordleError::DailyNotConfigured)?;
+                if !self.daily_epochs.contains_key(&epoch) {
+                    self.daily_epochs
+                        .retain(|played, _| played.saturating_add(DAILY_EPOCHS_KEPT) > epoch);
+                }
+                let results = self.daily_epochs.entry(epoch).or_default();
+                if !results.players.insert(user) {
+                    return Err(WordleError::DailyAlreadyPlayed { epoch });
+                }
+                let board = results.words.entry(language).or_insert_with(|| {
+                    let index = get_random_value(user, words.len() as u64);
+                    Board::new(words[index as usize], get_random_salt())
+                });
+                daily_word = board.word.clone();
+                (vec![daily_word.as_str()], Some(epoch))
+            }
+            // The answer is only settled by the guesses.
+            GameMode::Adversarial => (Vec::new(), None),
+        };
+        let game = Game {
+            candidates: (mode == GameMode::Adversarial).then(|| Candidates::full(words.len())),
+            daily_epoch,
+            ..Game::new(&hidden_words, hard_mode, language)
+        };
+        let commitments = self.add_game(user, game);
+        Ok(Event::GameStarted { user, commitments })
+    }
+
+    // Stores a new game and schedules its expiry, returns the word commitments.
+    fn add_game(&mut self, user: ActorId, mut game: Game) -> Vec<[u8; 32]> {
+        let game_id = self.games_started;
+        self.games_started += 1;
+        game.id = game_id;
+        let commitments = game.commitments();
+        // Replacing an existing game leaves its timer stale, it is ignored by `expire_game`.
+        self.games.insert(user, game);
+        msg::send_delayed(exec::program_id(), Action::ExpireGame { user, game_id }, 0, self.game_ttl)
+            .expect("Error in sending a delayed message");
+        commitments
+    }
+
+    fn check_word(&mut self, user: ActorId, word: String) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        let word = word.to_lowercase();
+        if word.chars().count() != WORD_LENGTH {
+            return Err(WordleError::InvalidWordLength {
+                expected: WORD_LENGTH as u8,
+            });
+        }
+        let game = self.games.get_mut(&user).ok_or(WordleError::GameNotFound)?;
+        game.check_caller()?;
+        if game.finished {
+            return Err(WordleError::GameOver);
+        }
+        let alphabet = alphabet(game.language);
+        if !word.chars().all(|letter| alphabet.contains(letter)) {
+            return Err(WordleError::InvalidLetters);
+        }
+        if let Err(violation) = game.check_hard_mode(&word) {
+            return Ok(Event::HardModeViolation { user, violation });
+        }
+
+        game.last_active_block = exec::block_height();
+        let mut feedback = Vec::with_capacity(game.boards.len().max(1));
+        if let Some(candidates) = &mut game.candidates {
+            feedback.push(candidates.narrow(dictionary(game.language), &word));
+        }
+        for board in &mut game.boards {
+            let board_feedback = check_against(&board.word, &word);
+            if board_feedback.correct_positions.len() == WORD_LENGTH {
+                board.solved = true;
+            }
+            feedback.push(board_feedback);
+        }
+
+        game.guesses.push(Guess {
+            word,
+            boards: feedback.clone(),
+        });
+
+        if game.is_solved() {
+            game.finished = true;
+            if let Some(epoch) = game.daily_epoch {
+                // The epoch may have been dropped while the game was played.
+                if let Some(results) = self.daily_epochs.get_mut(&epoch) {
+                    let guesses = game.guesses.len();
+                    if results.distribution.len() < guesses {
+                        results.distribution.resize(guesses, 0);
+                    }
+                    results.distribution[guesses - 1] += 1;
+                }
+            }
+            if let Some(room_id) = game.room_id {
+                let guesses = game.guesses.len() as u32;
+                if let Some(room) = self.rooms.get_mut(&room_id) {
+                    room.standings.push(RaceResult {
+                        player: user,
+                        guesses,
+                        block: exec::block_height(),
+                    });
+                    // Others may still solve it in this block, so the winner is picked in the next one.
+                    if room.standings.len() == 1 {
+                        msg::send_delayed(exec::program_id(), Action::FinishRace { room_id }, 0, 1)
+                            .expect("Error in sending a delayed message");
+                    }
+                }
+            }
+        }
+
+        if feedback.len() > 1 {
+            return Ok(Event::BoardsChecked {
+                user,
+                boards: feedback,
+            });
+        }
+        let BoardFeedback {
+            correct_positions,
+            contained_in_word,
+        } = feedback.remove(0);
+        Ok(Event::WordChecked {
+            user,
+            correct_positions,
+            contained_in_word,
+        })
+    }
+
+    fn reveal(&mut self, user: ActorId) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        let current_epoch = self.current_epoch();
+        let game = self.games.get_mut(&user).ok_or(WordleError::GameNotFound)?;
+        game.check_caller()?;
+        // Others are still playing the same daily word.
+        if let Some(epoch) = game.daily_epoch.filter(|epoch| current_epoch <= Some(*epoch)) {
+            return Err(WordleError::DailyInProgress { epoch });
+        }
+        // Rooms are removed once their race is finished.
+        if game.room_id.is_some_and(|room_id| self.rooms.contains_key(&room_id)) {
+            return Err(WordleError::RaceInProgress);
+        }
+        // Revealing ends the game, so the word cannot be used for further guesses.
+        game.finished = true;
+        let words = match &game.candidates {
+            Some(candidates) => candidates
+                .iter()
+                .map(|index| dictionary(game.language)[index].into())
+                .collect(),
+            None => game
+                .boards
+                .iter()
+                .map(|board| board.word.clone())
+                .collect(),
+        };
+        Ok(Event::WordRevealed {
+            user,
+            words,
+            salts: game.boards.iter().map(|board| board.salt).collect(),
+        })
+    }
+
+    fn end_game(&mut self, user: ActorId) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        self.games.get(&user).ok_or(WordleError::GameNotFound)?.check_caller()?;
+        self.games.remove(&user);
+        Ok(Event::GameEnded { user })
+    }
+
+    fn expire_game(&mut self, user: ActorId, game_id: u64) -> Result<Event, WordleError> {
+        if msg::source() != exec::program_id() {
+            return Err(WordleError::Unauthorized);
+        }
+        let game = self
+            .games
+            .get(&user)
+            .filter(|game| game.id == game_id)
+            .ok_or(WordleError::GameNotFound)?;
+        let expires_at = game.last_active_block.saturating_add(self.game_ttl);
+        let current_block = exec::block_height();
+        if current_block < expires_at {
+            // The game has been played since the timer was set, check again later.
+            let delay = expires_at - current_block;
+            msg::send_delayed(exec::program_id(), Action::ExpireGame { user, game_id }, 0, delay)
+                .expect("Error in sending a delayed message");
+            return Ok(Event::ExpiryPostponed { user, blocks: delay });
+        }
+        self.games.remove(&user);
+        Ok(Event::GameExpired { user })
+    }
+
+    fn create_room(&mut self, user: ActorId, language: Language) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        let room_id = self.rooms_created;
+        self.rooms_created += 1;
+        self.rooms.insert(
+            room_id,
+            Room {
+                host: user,
+                language,
+                participants: vec![user],
+                callers: [(user, msg::source())].into_iter().collect(),
+                started: false,
+                standings: Vec::new(),
+            },
+        );
+        Ok(Event::RoomCreated { room_id, host: user })
+    }
+
+    fn join_room(&mut self, user: ActorId, room_id: u64) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        // The race game replaces the current one, which only its caller may do.
+        if let Some(game) = self.games.get(&user).filter(|game| !game.finished) {
+            game.check_caller()?;
+        }
+        let room = self.rooms.get_mut(&room_id).ok_or(WordleError::RoomNotFound)?;
+        if room.started {
+            return Err(WordleError::RaceAlreadyStarted);
+        }
+        if room.participants.contains(&user) {
+            return Err(WordleError::AlreadyJoined);
+        }
+        if room.participants.len() >= MAX_RACE_PARTICIPANTS {
+            return Err(WordleError::RoomFull);
+        }
+        let event = Event::PlayerJoined {
+            room_id,
+            player: user,
+        };
+        broadcast(&room.participants, &event);
+        room.participants.push(user);
+        room.callers.insert(user, msg::source());
+        Ok(event)
+    }
+
+    fn start_race(&mut self, user: ActorId, room_id: u64) -> Result<Event, WordleError> {
+        self.authorize(user)?;
+        let room = self.rooms.get_mut(&room_id).ok_or(WordleError::RoomNotFound)?;
+        if room.host != user {
+            return Err(WordleError::NotHost);
+        }
+        if room.started {
+            return Err(WordleError::RaceAlreadyStarted);
+        }
+        // A participant may have started a game through another sender since joining.
+        let busy = room.participants.iter().find(|participant| {
+            self.games
+                .get(*participant)
+                .is_some_and(|game| !game.finished && game.caller != room.callers[*participant])
+        });
+        if let Some(&player) = busy {
+            return Err(WordleError::ParticipantBusy { player });
+        }
+        room.started = true;
+        let participants = room.participants.clone();
+        let callers = room.callers.clone();
+        let language = room.language;
+        let words = dictionary(language);
+        let word = words[get_random_value(user, words.len() as u64) as usize];
+
+        let mut commitment = [0; 32];
+        for participant in &participants {
+            let game = Game {
+                caller: callers[participant],
+                room_id: Some(room_id),
+                ..Game::new(&[word], false, language)
+            };
+            commitment = self.add_game(*participant, game)[0];
+        }
+        msg::send_delayed(exec::program_id(), Action::FinishRace { room_id }, 0, RACE_DURATION)
+            .expect("Error in sending a delayed message");
+        let event = Event::RaceStarted {
+            room_id,
+            commitment,
+        };
+        broadcast(&participants, &event);
+        Ok(event)
+    }
+
+    fn finish_race(&mut self, room_id: u64) -> Result<Event, WordleError> {
+        if msg::source() != exec::program_id() {
+            return Err(WordleError::Unauthorized);
+        }
+        // The race is finished by the first solve or by its deadline, whichever comes first.
+        let mut room = self.rooms.remove(&room_id).ok_or(WordleError::RoomNotFound)?;
+        // The earliest block wins, then the fewest guesses, then the first to arrive.
+        room.standings.sort_by_key(|result| (result.block, result.guesses));
+        let event = Event::RaceFinished {
+            room_id,
+            winner: room.standings.first().map(|result| result.player),
+            standings: room.standings,
+        };
+        broadcast(&room.participants, &event);
+        Ok(event)
+    }
+
+    fn set_game_ttl(&mut self, blocks: u32) -> Result<Event, WordleError> {
+        self.check_owner()?;
+        if blocks == 0 {
+            return Err(WordleError::InvalidGameTtl);
+        }
+        self.game_ttl = blocks;
+        Ok(Event::GameTtlSet { blocks })
+    }
+
+    fn set_daily_config(&mut self, epoch_blocks: u32) -> Result<Event, WordleError> {
+        self.check_owner()?;
+        if epoch_blocks == 0 {
+            return Err(WordleError::InvalidEpochLength);
+        }
+        // The current epoch ends right away, its players keep their word.
+        let first_epoch = self.current_epoch().map_or(0, |epoch| epoch + 1);
+        self.daily_config = Some(DailyConfig {
+            epoch_blocks,
+            start_block: exec::block_height(),
+            first_epoch,
+        });
+        Ok(Event::DailyConfigSet { epoch_blocks })
+    }
+
+    fn current_epoch(&self) -> Option<u32> {
+        self.daily_config.as_ref().map(|config| {
+            config.first_epoch + (exec::block_height() - config.start_block) / config.epoch_blocks
+        })
+    }
+
+    fn add_proxy(&mut self, proxy: ActorId) -> Result<Event, WordleError> {
+        self.check_owner()?;
+        self.proxies.insert(proxy);
+        Ok(Event::ProxyAdded { proxy })
+    }
+
+    fn remove_proxy(&mut self, proxy: ActorId) -> Result<Event, WordleError> {
+        self.check_owner()?;
+        self.proxies.remove(&proxy);
+        Ok(Event::ProxyRemoved { proxy })
+    }
+
+    fn daily_stats(&self, epoch: u32) -> Result<Event, WordleError> {
+        let (players, distribution) = self
+            .daily_epochs
+            .get(&epoch)
+            .map(|results| (results.players.len() as u32, results.distribution.clone()))
+            .unwrap_or_default();
+        Ok(Event::DailyStats {
+            epoch,
+            players,
+            distribution,
+        })
+    }
 }
 
 #[no_mangle]
 extern "C" fn init() {
     unsafe {
         WORDLE = Some(Wordle {
-            games: HashMap::new(),
+            owner: msg::source(),
+            game_ttl: DEFAULT_GAME_TTL,
+            ..Default::default()
         });
     }
 }
 
 #[no_mangle]
 extern "C" fn handle() {
-    let action: Action = msg::load().expect("Unable to decode ");
     let wordle = unsafe { WORDLE.as_mut().expect("The program is not initialized") };
+    let reply = match msg::load() {
+        Ok(Action::StartGame {
+            user,
+            hard_mode,
+            mode,
+            language,
+        }) => wordle.start_game(user, hard_mode, mode, language),
+        Ok(Action::CheckWord { user, word }) => wordle.check_word(user, word),
+        Ok(Action::Reveal { user }) => wordle.reveal(user),
+        Ok(Action::EndGame { user }) => wordle.end_game(user),
+        Ok(Action::ExpireGame { user, game_id }) => wordle.expire_game(user, game_id),
+        Ok(Action::SetGameTtl { blocks }) => wordle.set_game_ttl(blocks),
+        Ok(Action::CreateRoom { user, language }) => wordle.create_room(user, language),
+        Ok(Action::JoinRoom { user, room_id }) => wordle.join_room(user, room_id),
+        Ok(Action::StartRace { user, room_id }) => wordle.start_race(user, room_id),
+        Ok(Action::FinishRace { room_id }) => wordle.finish_race(room_id),
+        Ok(Action::AddProxy { proxy }) => wordle.add_proxy(proxy),
+        Ok(Action::RemoveProxy { proxy }) => wordle.remove_proxy(proxy),
+        Ok(Action::SetDailyConfig { epoch_blocks }) => wordle.set_daily_config(epoch_blocks),
+        Ok(Action::DailyStats { epoch }) => wordle.daily_stats(epoch),
+        Err(_) => Err(WordleError::InvalidAction),
+    };
 
-    let reply = match action {
-        Action::StartGame { user } => {
-            let random_id = get_random_value(BANK_OF_WORDS.len() as u8);
-            let word = BANK_OF_WORDS[random_id as usize];
-            wordle.games.insert(user, word.to_string());
-            Event::GameStarted { user }
-        }
-        Action::CheckWord { user, word } => {
-            if word.len() != 5 {
-                panic!("The length of the word exceeds 5");
-            }
-            let key_word = wordle
+    msg::reply(reply, 0).expect("Error in sending a reply");
+}
+
+#[no_mangle]
+extern "C" fn state() {
+    let wordle = unsafe { WORDLE.as_ref().expect("State is not existing") };
+    let query: StateQuery = msg::load().expect("Unable to decode `StateQuery`");
+    let reply = match query {
+        StateQuery::All => StateReply::All(WordleState {
+            owner: wordle.owner,
+            proxies: wordle.proxies.iter().copied().collect(),
+            live_games: wordle.games.len() as u32,
+            active_games: wordle.games.values().filter(|game| !game.finished).count() as u32,
+            games_started: wordle.games_started,
+            game_ttl: wordle.game_ttl,
+            games: wordle
                 .games
-                .get(&user)
-                .expect("There is no game with this user");
-            let mut matched_indices = Vec::with_capacity(5);
-            let mut key_indices = Vec::with_capacity(5);
-            for (i, (a, b)) in key_word.chars().zip(word.chars()).enumerate() {
-                if a == b {
-                    matched_indices.push(i as u8);
-                } else if key_word.contains(b) {
-                    key_indices.push(i as u8);
-                }
-            }
+                .iter()
+                .map(|(user, game)| (*user, game.info()))
+                .collect(),
+            dictionaries: LANGUAGES
+                .iter()
+                .map(|language| (*language, dictionary(*language).len() as u32))
+                .collect(),
+            daily_epoch_blocks: wordle.daily_config.as_ref().map(|config| config.epoch_blocks),
+        }),
+        StateQuery::Game(user) => StateReply::Game(wordle.games.get(&user).map(Game::info)),
+        StateQuery::Room(room_id) => StateReply::Room(wordle.rooms.get(&room_id).map(Room::info)),
+    };
+    msg::reply(reply, 0).expect("Unable to get the state");
+}
 
-            Event::WordChecked {
-                user,
-                correct_positions: matched_indices,
-                contained_in_word: key_indices,
-            }
+fn check_against(key_word: &str, word: &str) -> BoardFeedback {
+    let mut matched_indices = Vec::with_capacity(WORD_LENGTH);
+    let mut key_indices = Vec::with_capacity(WORD_LENGTH);
+    for (i, (a, b)) in key_word.chars().zip(word.chars()).enumerate() {
+        if a == b {
+            matched_indices.push(i as u8);
+        } else if key_word.contains(b) {
+            key_indices.push(i as u8);
         }
-    };
+    }
+    BoardFeedback {
+        correct_positions: matched_indices,
+        contained_in_word: key_indices,
+    }
+}
 
-    msg::reply(reply, 0).expect("Error in sending a reply");
+// Packs the feedback into a bitmask: correct positions in the low bits, then
+// the positions of contained letters.
+fn feedback_pattern(feedback: &BoardFeedback) -> usize {
+    let mask = |positions: &[u8]| positions.iter().fold(0, |bits, position| bits | 1 << position);
+    mask(&feedback.correct_positions) | mask(&feedback.contained_in_word) << WORD_LENGTH
+}
+
+// Notifies room participants, except the sender who gets the reply instead.
+// Notifications are encoded like replies, so clients decode a single type.
+fn broadcast(participants: &[ActorId], event: &Event) {
+    let source = msg::source();
+    let notification: Result<Event, WordleError> = Ok(event.clone());
+    for participant in participants.iter().filter(|participant| **participant != source) {
+        msg::send(*participant, &notification, 0).expect("Error in sending a message");
+    }
+}
+
+static mut SEED: u64 = 0;
+
+// Draws an index below `range`. The random subject mixes the message, the user,
+// the block and a counter, so several draws in one block still differ.
+pub fn get_random_value(user: ActorId, range: u64) -> u64 {
+    sample_below(range, || {
+        let seed = unsafe { SEED };
+        unsafe { SEED = SEED.wrapping_add(1) };
+        let message_id: [u8; 32] = msg::id().into();
+        let user: [u8; 32] = user.into();
+        let mut hasher = Sha256::new();
+        hasher.update(message_id);
+        hasher.update(user);
+        hasher.update(exec::block_height().to_le_bytes());
+        hasher.update(exec::block_timestamp().to_le_bytes());
+        hasher.update(seed.to_le_bytes());
+        let (random, _) = exec::random(hasher.finalize().into()).expect("Error in getting random number");
+        u64::from_le_bytes(random[..8].try_into().expect("Random value is too short"))
+    })
+}
+
+// Maps uniform `u64` draws to `0..range` without modulo bias: draws from the
+// incomplete last bucket are rejected and drawn again.
+fn sample_below(range: u64, mut draw: impl FnMut() -> u64) -> u64 {
+    assert!(range > 0, "Cannot sample from an empty range");
+    let zone = u64::MAX - u64::MAX % range;
+    loop {
+        let value = draw();
+        if value < zone {
+            return value % range;
+        }
+    }
+}
+
+fn get_random_salt() -> [u8; 32] {
+    let (random, _) = exec::random(msg::id().into()).expect("Error in getting random number");
+    random
 }
 
-static mut SEED: u8 = 0;
+// Gives every board its own salt, so boards never share a commitment.
+fn get_board_salt(salt: &[u8; 32], board: usize) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update((board as u32).to_le_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_below_rejects_draws_from_the_last_bucket() {
+        // `u64::MAX % range` is `range - 3`, so every draw from `range` up is rejected.
+        let range = (1 << 63) + 1;
+        let mut draws = [range, u64::MAX, 7].into_iter();
+        assert_eq!(sample_below(range, || draws.next().expect("Too many draws")), 7);
+        assert_eq!(draws.next(), None);
 
-pub fn get_random_value(range: u8) -> u8 {
-    let seed = unsafe { SEED };
-    unsafe { SEED = SEED.wrapping_add(1) };
-    let mut random_input: [u8; 32] = exec::program_id().into();
-    random_input[0] = random_input[0].wrapping_add(seed);
-    let (random, _) = exec::random(random_input).expect("Error in getting random number");
-    random[0] % range
+        // With a divisor of `u64::MAX` only the largest draw is rejected.
+        let mut draws = [u64::MAX, 5].into_iter();
+        assert_eq!(sample_below(3, || draws.next().expect("Too many draws")), 2);
+        assert_eq!(draws.next(), None);
+    }
+
+    #[test]
+    fn sample_below_covers_ranges_over_a_byte() {
+        for (range, draw, index) in [(300, 299, 299), (300, 300, 0), (1_000, 1_234, 234), (1 << 40, (1 << 40) + 9, 9)] {
+            let mut draws = [draw].into_iter();
+            assert_eq!(sample_below(range, || draws.next().expect("Too many draws")), index);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot sample from an empty range")]
+    fn sample_below_needs_a_range() {
+        sample_below(0, || 0);
+    }
 }
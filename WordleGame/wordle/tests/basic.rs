@@ -0,0 +1,506 @@
+use gstd::prelude::*;
+use gtest::{Log, Program, RunResult, System};
+use wordle_io::*;
+
+const OWNER: u64 = 10;
+const USER2: u64 = 11;
+const USER3: u64 = 12;
+
+const WORDS: [&str; 3] = ["house", "human", "horse"];
+
+fn init_wordle(system: &System) -> Program<'_> {
+    let program = Program::current(system);
+    let result = program.send_bytes(OWNER, []);
+    assert!(!result.main_failed());
+    program
+}
+
+// Picks the reply to `user` out of the messages the program sent.
+fn reply(result: &RunResult, user: u64) -> Result<Event, WordleError> {
+    let log = result
+        .log()
+        .iter()
+        .find(|log| log.destination() == user.into())
+        .expect("There is no reply");
+    Result::decode(&mut log.payload()).expect("Unable to decode the reply")
+}
+
+fn replied(user: u64, reply: Result<Event, WordleError>) -> Log {
+    Log::builder().dest(user).payload(reply)
+}
+
+fn start_game(wordle: &Program, user: u64, language: Language) {
+    let result = wordle.send(user, Action::StartGame { user: user.into(), hard_mode: false, mode: GameMode::Classic, language });
+    assert!(matches!(reply(&result, user), Ok(Event::GameStarted { .. })));
+}
+
+fn solved(user: u64) -> Log {
+    replied(
+        user,
+        Ok(Event::WordChecked {
+            user: user.into(),
+            correct_positions: vec![0, 1, 2, 3, 4],
+            contained_in_word: vec![],
+        }),
+    )
+}
+
+#[test]
+fn test_daily_puzzle_is_shared_and_played_once() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(USER2, Action::SetDailyConfig { epoch_blocks: 100 });
+    assert!(result.contains(&replied(USER2, Err(WordleError::NotOwner))));
+
+    let result = wordle.send(OWNER, Action::SetDailyConfig { epoch_blocks: 100 });
+    assert!(result.contains(&replied(OWNER, Ok(Event::DailyConfigSet { epoch_blocks: 100 }))));
+
+    for user in [OWNER, USER2] {
+        let result = wordle.send(user, Action::StartGame { user: user.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English });
+        assert!(matches!(reply(&result, user), Ok(Event::GameStarted { .. })));
+    }
+
+    let mut owner_guesses = 0;
+    let word = WORDS
+        .into_iter()
+        .find(|word| {
+            owner_guesses += 1;
+            wordle
+                .send(OWNER, Action::CheckWord { user: OWNER.into(), word: word.to_string() })
+                .contains(&solved(OWNER))
+        })
+        .expect("One of the words must be the daily puzzle");
+
+    // The second player gets the same word.
+    let result = wordle.send(USER2, Action::CheckWord { user: USER2.into(), word: word.to_string() });
+    assert!(result.contains(&solved(USER2)));
+
+    let result = wordle.send(OWNER, Action::StartGame { user: OWNER.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::DailyAlreadyPlayed { epoch: 0 }))));
+
+    let mut distribution = vec![0; owner_guesses];
+    distribution[0] += 1;
+    distribution[owner_guesses - 1] += 1;
+    let result = wordle.send(OWNER, Action::DailyStats { epoch: 0 });
+    assert!(result.contains(&replied(OWNER, Ok(Event::DailyStats { epoch: 0, players: 2, distribution }))));
+
+    // The word is not revealed while others may still be playing it.
+    let result = wordle.send(USER2, Action::Reveal { user: USER2.into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::DailyInProgress { epoch: 0 }))));
+
+    system.spend_blocks(100);
+    let result = wordle.send(USER2, Action::Reveal { user: USER2.into() });
+    let Ok(Event::WordRevealed { words, .. }) = reply(&result, USER2) else {
+        panic!("Unexpected reply to `Reveal`");
+    };
+    assert_eq!(words, vec![word.to_string()]);
+
+    let result = wordle.send(OWNER, Action::StartGame { user: OWNER.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English });
+    assert!(matches!(reply(&result, OWNER), Ok(Event::GameStarted { .. })));
+
+    // A new config ends the current epoch instead of renumbering the played ones.
+    wordle.send(OWNER, Action::SetDailyConfig { epoch_blocks: 1_000 });
+    let result = wordle.send(OWNER, Action::Reveal { user: OWNER.into() });
+    assert!(matches!(reply(&result, OWNER), Ok(Event::WordRevealed { .. })));
+    let result = wordle.send(USER2, Action::StartGame { user: USER2.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English });
+    assert!(matches!(reply(&result, USER2), Ok(Event::GameStarted { .. })));
+    let StateReply::Game(Some(game)) = wordle.read_state(StateQuery::Game(USER2.into())).unwrap() else {
+        panic!("The daily game must be in the state");
+    };
+    assert_eq!(game.daily_epoch, Some(2));
+}
+
+#[test]
+fn test_revealed_word_matches_commitment() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(OWNER, Action::StartGame { user: OWNER.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English });
+    let Ok(Event::GameStarted { commitments, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `StartGame`");
+    };
+
+    let result = wordle.send(OWNER, Action::Reveal { user: OWNER.into() });
+    let Ok(Event::WordRevealed { words, salts, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `Reveal`");
+    };
+    assert_eq!(words.len(), 1);
+    assert!(WORDS.contains(&words[0].as_str()));
+    assert_eq!(vec![word_commitment(&words[0], &salts[0])], commitments);
+
+    // The game is over once the word is revealed.
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: words[0].clone() });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::GameOver))));
+}
+
+#[test]
+fn test_state_hides_the_word() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let state: StateReply = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap();
+    assert_eq!(state, StateReply::Game(None));
+
+    let result = wordle.send(OWNER, Action::StartGame { user: OWNER.into(), hard_mode: true, mode: GameMode::Classic, language: Language::English });
+    let Ok(Event::GameStarted { commitments, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `StartGame`");
+    };
+    wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "hzzzz".into() });
+
+    let StateReply::Game(Some(game)) = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap() else {
+        panic!("The game must be in the state");
+    };
+    assert_eq!(game.commitments, commitments);
+    assert!(game.hard_mode);
+    assert!(!game.finished);
+    assert_eq!(
+        game.guesses,
+        vec![Guess {
+            word: "hzzzz".into(),
+            boards: vec![BoardFeedback { correct_positions: vec![0], contained_in_word: vec![] }],
+        }]
+    );
+
+    let StateReply::All(state) = wordle.read_state(StateQuery::All).unwrap() else {
+        panic!("Unexpected state reply");
+    };
+    assert_eq!(state.active_games, 1);
+    assert_eq!(state.dictionaries[0], (Language::English, 3));
+    assert_eq!(state.games.len(), 1);
+}
+
+#[test]
+fn test_multibyte_letters() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    start_game(&wordle, OWNER, Language::Russian);
+
+    // Letters outside of the game's alphabet are rejected.
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "house".into() });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::InvalidLetters))));
+
+    // "книга" and "школа" end with "а" and "слово" has none: the feedback is indexed
+    // by letter, not by byte.
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "ААААА".into() });
+    let Ok(Event::WordChecked { correct_positions, contained_in_word, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `CheckWord`");
+    };
+    assert!(
+        (correct_positions == vec![4] && contained_in_word == vec![0, 1, 2, 3])
+            || (correct_positions.is_empty() && contained_in_word.is_empty())
+    );
+
+    let solved_with = ["книга", "школа", "слово"]
+        .into_iter()
+        .find(|word| {
+            wordle
+                .send(OWNER, Action::CheckWord { user: OWNER.into(), word: word.to_uppercase() })
+                .contains(&solved(OWNER))
+        });
+    assert!(solved_with.is_some());
+
+    start_game(&wordle, USER2, Language::Spanish);
+    let solved_with = ["señor", "sueño", "dueño"]
+        .into_iter()
+        .find(|word| {
+            wordle
+                .send(USER2, Action::CheckWord { user: USER2.into(), word: word.to_string() })
+                .contains(&solved(USER2))
+        });
+    assert!(solved_with.is_some());
+}
+
+#[test]
+fn test_errors_are_replied() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "house".into() });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::GameNotFound))));
+
+    start_game(&wordle, OWNER, Language::English);
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "hut".into() });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::InvalidWordLength { expected: 5 }))));
+
+    let result = wordle.send_bytes(OWNER, [0xff]);
+    assert!(result.contains(&replied(OWNER, Err(WordleError::InvalidAction))));
+
+    let result = wordle.send(OWNER, Action::StartGame { user: OWNER.into(), hard_mode: false, mode: GameMode::Daily, language: Language::English });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::DailyNotConfigured))));
+}
+
+#[test]
+fn test_only_users_and_proxies_can_play() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let start = Action::StartGame { user: OWNER.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English };
+    let result = wordle.send(USER2, start.clone());
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+
+    let result = wordle.send(USER2, Action::AddProxy { proxy: USER2.into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::NotOwner))));
+
+    let result = wordle.send(OWNER, Action::AddProxy { proxy: USER2.into() });
+    assert!(result.contains(&replied(OWNER, Ok(Event::ProxyAdded { proxy: USER2.into() }))));
+    let result = wordle.send(USER2, start);
+    assert!(matches!(reply(&result, USER2), Ok(Event::GameStarted { .. })));
+
+    wordle.send(OWNER, Action::RemoveProxy { proxy: USER2.into() });
+    let result = wordle.send(USER2, Action::CheckWord { user: OWNER.into(), word: "house".into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+}
+
+#[test]
+fn test_proxy_games_are_only_played_through_the_proxy() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    wordle.send(OWNER, Action::AddProxy { proxy: USER3.into() });
+    let result = wordle.send(USER3, Action::StartGame { user: USER2.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English });
+    assert!(matches!(reply(&result, USER3), Ok(Event::GameStarted { .. })));
+
+    // The player cannot guess around the proxy, nor replace or end its game.
+    let result = wordle.send(USER2, Action::CheckWord { user: USER2.into(), word: "house".into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+    let result = wordle.send(USER2, Action::Reveal { user: USER2.into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+    let result = wordle.send(USER2, Action::StartGame { user: USER2.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+    let result = wordle.send(USER2, Action::EndGame { user: USER2.into() });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+
+    let result = wordle.send(USER3, Action::CheckWord { user: USER2.into(), word: "hzzzz".into() });
+    assert!(matches!(reply(&result, USER3), Ok(Event::WordChecked { .. })));
+
+    // Once the proxy ends its game, the player may start one of their own.
+    wordle.send(USER3, Action::EndGame { user: USER2.into() });
+    start_game(&wordle, USER2, Language::English);
+}
+
+#[test]
+fn test_idle_games_expire() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(OWNER, Action::SetGameTtl { blocks: 10 });
+    assert!(result.contains(&replied(OWNER, Ok(Event::GameTtlSet { blocks: 10 }))));
+
+    start_game(&wordle, OWNER, Language::English);
+    system.spend_blocks(5);
+    wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "hzzzz".into() });
+
+    // The guess postponed the expiry.
+    system.spend_blocks(5);
+    let StateReply::All(state) = wordle.read_state(StateQuery::All).unwrap() else {
+        panic!("Unexpected state reply");
+    };
+    assert_eq!(state.live_games, 1);
+
+    system.spend_blocks(10);
+    let state: StateReply = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap();
+    assert_eq!(state, StateReply::Game(None));
+
+    // The timer cannot be triggered from outside.
+    start_game(&wordle, OWNER, Language::English);
+    let result = wordle.send(OWNER, Action::ExpireGame { user: OWNER.into(), game_id: 1 });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::Unauthorized))));
+
+    let result = wordle.send(OWNER, Action::EndGame { user: OWNER.into() });
+    assert!(result.contains(&replied(OWNER, Ok(Event::GameEnded { user: OWNER.into() }))));
+    let StateReply::All(state) = wordle.read_state(StateQuery::All).unwrap() else {
+        panic!("Unexpected state reply");
+    };
+    assert_eq!(state.live_games, 0);
+    assert_eq!(state.games_started, 2);
+}
+
+#[test]
+fn test_race_is_won_by_the_first_solver() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(OWNER, Action::CreateRoom { user: OWNER.into(), language: Language::English });
+    assert!(result.contains(&replied(OWNER, Ok(Event::RoomCreated { room_id: 0, host: OWNER.into() }))));
+
+    let result = wordle.send(USER2, Action::JoinRoom { user: USER2.into(), room_id: 0 });
+    assert!(result.contains(&replied(USER2, Ok(Event::PlayerJoined { room_id: 0, player: USER2.into() }))));
+    // The host is told about the new participant.
+    assert!(result.contains(&Log::builder().dest(OWNER).payload(Ok::<_, WordleError>(Event::PlayerJoined { room_id: 0, player: USER2.into() }))));
+
+    let result = wordle.send(USER2, Action::StartRace { user: USER2.into(), room_id: 0 });
+    assert!(result.contains(&replied(USER2, Err(WordleError::NotHost))));
+
+    let result = wordle.send(OWNER, Action::StartRace { user: OWNER.into(), room_id: 0 });
+    let Ok(Event::RaceStarted { commitment, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `StartRace`");
+    };
+    assert!(result.contains(&Log::builder().dest(USER2).payload(Ok::<_, WordleError>(Event::RaceStarted { room_id: 0, commitment }))));
+
+    let result = wordle.send(USER3, Action::JoinRoom { user: USER3.into(), room_id: 0 });
+    assert!(result.contains(&replied(USER3, Err(WordleError::RaceAlreadyStarted))));
+
+    // The shared word stays hidden while the race is on.
+    let result = wordle.send(OWNER, Action::Reveal { user: OWNER.into() });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::RaceInProgress))));
+
+    let solved_with = WORDS.into_iter().find(|word| {
+        wordle
+            .send(USER2, Action::CheckWord { user: USER2.into(), word: word.to_string() })
+            .contains(&solved(USER2))
+    });
+    assert!(solved_with.is_some());
+    let StateReply::Room(Some(room)) = wordle.read_state(StateQuery::Room(0)).unwrap() else {
+        panic!("The room must be in the state");
+    };
+    assert_eq!(room.standings.len(), 1);
+    assert_eq!(room.participants, vec![OWNER.into(), USER2.into()]);
+
+    let results = system.spend_blocks(1);
+    let Ok(Event::RaceFinished { winner, standings, .. }) = reply(&results[0], OWNER) else {
+        panic!("The race must be finished in the next block");
+    };
+    assert_eq!(winner, Some(USER2.into()));
+    assert_eq!(standings.len(), 1);
+    // Finished rooms are removed.
+    assert_eq!(wordle.read_state(StateQuery::Room(0)).unwrap(), StateReply::Room(None));
+
+    let result = wordle.send(OWNER, Action::Reveal { user: OWNER.into() });
+    assert!(matches!(reply(&result, OWNER), Ok(Event::WordRevealed { .. })));
+}
+
+#[test]
+fn test_unsolved_race_ends_at_the_deadline() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+    wordle.send(OWNER, Action::AddProxy { proxy: USER3.into() });
+
+    wordle.send(OWNER, Action::CreateRoom { user: OWNER.into(), language: Language::English });
+    wordle.send(USER2, Action::JoinRoom { user: USER2.into(), room_id: 0 });
+
+    // The race would replace a game the proxy plays for a participant.
+    let result = wordle.send(USER3, Action::StartGame { user: USER2.into(), hard_mode: false, mode: GameMode::Classic, language: Language::English });
+    assert!(matches!(reply(&result, USER3), Ok(Event::GameStarted { .. })));
+    let result = wordle.send(OWNER, Action::StartRace { user: OWNER.into(), room_id: 0 });
+    assert!(result.contains(&replied(OWNER, Err(WordleError::ParticipantBusy { player: USER2.into() }))));
+    wordle.send(OWNER, Action::CreateRoom { user: OWNER.into(), language: Language::English });
+    let result = wordle.send(USER2, Action::JoinRoom { user: USER2.into(), room_id: 1 });
+    assert!(result.contains(&replied(USER2, Err(WordleError::Unauthorized))));
+
+    wordle.send(USER3, Action::EndGame { user: USER2.into() });
+    let result = wordle.send(OWNER, Action::StartRace { user: OWNER.into(), room_id: 0 });
+    assert!(matches!(reply(&result, OWNER), Ok(Event::RaceStarted { .. })));
+
+    let results = system.spend_blocks(100);
+    let finished = Log::builder().dest(OWNER).payload(Ok::<_, WordleError>(Event::RaceFinished {
+        room_id: 0,
+        winner: None,
+        standings: vec![],
+    }));
+    assert!(results.iter().any(|result| result.contains(&finished)));
+    assert_eq!(wordle.read_state(StateQuery::Room(0)).unwrap(), StateReply::Room(None));
+
+    let result = wordle.send(USER2, Action::Reveal { user: USER2.into() });
+    assert!(matches!(reply(&result, USER2), Ok(Event::WordRevealed { .. })));
+}
+
+#[test]
+fn test_multi_board_game_is_won_when_every_board_is_solved() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let start = |boards| Action::StartGame {
+        user: OWNER.into(),
+        hard_mode: false,
+        mode: GameMode::MultiBoard { boards },
+        language: Language::English,
+    };
+    let result = wordle.send(OWNER, start(3));
+    assert!(result.contains(&replied(OWNER, Err(WordleError::InvalidBoardCount))));
+
+    let result = wordle.send(OWNER, start(4));
+    let Ok(Event::GameStarted { commitments, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `StartGame`");
+    };
+    assert_eq!(commitments.len(), 4);
+
+    // Every board hides one of the dictionary words, so guessing all of them wins.
+    for word in WORDS {
+        let StateReply::Game(Some(game)) = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap() else {
+            panic!("The game must be in the state");
+        };
+        if game.finished {
+            break;
+        }
+
+        let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: word.into() });
+        let Ok(Event::BoardsChecked { boards, .. }) = reply(&result, OWNER) else {
+            panic!("Unexpected reply to `CheckWord`");
+        };
+        assert_eq!(boards.len(), 4);
+    }
+
+    let StateReply::Game(Some(game)) = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap() else {
+        panic!("The game must be in the state");
+    };
+    assert_eq!(game.solved_boards, vec![true; 4]);
+    assert!(game.finished);
+}
+
+#[test]
+fn test_adversarial_game_dodges_guesses() {
+    let system = System::new();
+    system.init_logger();
+    let wordle = init_wordle(&system);
+
+    let result = wordle.send(
+        OWNER,
+        Action::StartGame {
+            user: OWNER.into(),
+            hard_mode: false,
+            mode: GameMode::Adversarial,
+            language: Language::English,
+        },
+    );
+    assert!(result.contains(&replied(OWNER, Ok(Event::GameStarted { user: OWNER.into(), commitments: vec![] }))));
+
+    let game_info = || {
+        let StateReply::Game(Some(game)) = wordle.read_state(StateQuery::Game(OWNER.into())).unwrap() else {
+            panic!("The game must be in the state");
+        };
+        game
+    };
+    assert_eq!(game_info().candidates_left, Some(WORDS.len() as u32));
+
+    // Every word gives a different feedback to "house", so the adversary keeps
+    // one of the other words instead of giving in.
+    let result = wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: "house".into() });
+    let Ok(Event::WordChecked { correct_positions, .. }) = reply(&result, OWNER) else {
+        panic!("Unexpected reply to `CheckWord`");
+    };
+    assert_ne!(correct_positions.len(), 5);
+    let game = game_info();
+    assert_eq!(game.candidates_left, Some(1));
+    assert!(!game.finished);
+
+    for word in ["human", "horse"] {
+        wordle.send(OWNER, Action::CheckWord { user: OWNER.into(), word: word.into() });
+        if game_info().finished {
+            break;
+        }
+    }
+    assert!(game_info().finished);
+}